@@ -0,0 +1,149 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use editorial_voting_vercel_serverless_function::{atcoder_api, config, database, editorials, error, metrics};
+use vercel_runtime::{process_request, process_response, run_service, Body, Error, Request, RequestPayloadExt, Response, ServiceBuilder, StatusCode};
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+struct Req {
+    sort: Option<String>,
+    author: Option<String>,
+    offset: Option<i64>,
+    limit: Option<i64>,
+}
+
+#[derive(serde::Serialize, Default, Debug)]
+struct Res {
+    status: &'static str,
+    reason: Option<String>,
+    code: Option<&'static str>,
+    results: Option<Vec<AuthorRes>>,
+    next_offset: Option<i64>,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct AuthorRes {
+    author: String,
+    editorials: i64,
+    score: i64,
+    up_votes: i64,
+    down_votes: i64,
+    average_score: f64,
+    contests: Vec<ContestRes>,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct ContestRes {
+    contest: Option<String>,
+    editorials: i64,
+    score: i64,
+    up_votes: i64,
+    down_votes: i64,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let handler = ServiceBuilder::new()
+        .map_request(process_request)
+        .map_response(process_response)
+        .service_fn(handler);
+
+    run_service(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    if req.method() == "OPTIONS" {
+        return Ok(Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .header("Access-Control-Allow-Origin", "*")
+            .header("Access-Control-Allow-Methods", "*")
+            .header("Access-Control-Allow-Headers", "*")
+            .header("Access-Control-Max-Age", "86400")
+            .body(Body::Empty)?);
+    }
+    let started = std::time::Instant::now();
+    let res = match proc(req).await {
+        Ok(res) => res,
+        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), code: error::error_code(&*reason), .. Default::default() },
+    };
+    metrics::record_request("authors", started, res.status, res.code);
+    metrics::flush().await;
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/json")
+        .header("Access-Control-Allow-Origin", "*")
+        .header("Access-Control-Allow-Headers", "*")
+        .body(Body::Text(serde_json::to_string(&res)?))?)
+}
+
+async fn proc(req: Request) -> Result<Res, Box<dyn std::error::Error>> {
+    let Ok(Some(req)) = req.payload::<Req>() else {
+        return Err("invalid request".into());
+    };
+
+    let order = match req.sort.as_deref().unwrap_or("score") {
+        "score" => "SUM(score) DESC",
+        "average" => "SUM(score)::DOUBLE PRECISION / COUNT(*) DESC",
+        "editorials" => "COUNT(*) DESC",
+        "votes" => "SUM(up) + SUM(down) DESC",
+        _ => return Err("invalid sort (score|average|editorials|votes)".into()),
+    };
+    if req.author.as_deref().is_some_and(|author| !atcoder_api::validate_atcoder_id(author) ) {
+        return Err("author invalid format".into());
+    }
+    let offset = req.offset.unwrap_or(0);
+    if offset < 0 {
+        return Err("offset must not be negative".into());
+    }
+    let limit = req.limit.unwrap_or(50);
+    if !(1 ..= 200).contains(&limit) {
+        return Err("limit must be between 1 and 200".into());
+    }
+
+    fn use_db(mut client: postgres::Client, (req, order, offset, limit): (Req, &'static str, i64, i64)) -> Result<Res, Box<dyn std::error::Error>> {
+        // total votes of each editorial, excluding contests not ended yet if scores are hidden during contests
+        // same as contests::hides_scores: contests not cached or running (except permanent ones) are hidden
+        // authors are compared case-insensitively (`author_key`) like AtCoder IDs
+        let hide_running = config::hide_scores_during_contest()?;
+        let editorial_scores = format!("SELECT editorials.id, LOWER(author) AS author_key, author, contest, COALESCE(SUM(vote_temp.score), 0) AS score, COALESCE(SUM(up), 0) AS up, COALESCE(SUM(down), 0) AS down FROM editorials LEFT JOIN vote_temp ON vote_temp.editorial_id = editorials.id WHERE author IS NOT NULL AND ($1::TEXT IS NULL OR LOWER(author) = LOWER($1)) AND {} AND NOT ($2 AND editorials.contest IS NOT NULL AND NOT EXISTS (SELECT 1 FROM contests WHERE contests.contest = editorials.contest AND (contests.end_time <= $3 OR contests.end_time - contests.start_time > INTERVAL '365 days'))) GROUP BY editorials.id", editorials::VISIBLE_SQL);
+        let params: [&(dyn postgres::types::ToSql + Sync); 3] = [&req.author, &hide_running, &SystemTime::now()];
+
+        let rows = client.query(&format!("SELECT MIN(author), COUNT(*) AS editorials, SUM(score)::BIGINT AS score, SUM(up)::BIGINT AS up, SUM(down)::BIGINT AS down FROM ({editorial_scores}) AS editorial_scores GROUP BY author_key ORDER BY {order}, author_key LIMIT $4 OFFSET $5"), &[params[0], params[1], params[2], &(limit + 1), &offset])?;
+        let next_offset = if rows.len() as i64 > limit { Some(offset + limit) } else { None };
+        let mut results = rows.iter().take(limit as usize).map(|row| {
+            let editorials = row.get::<_, i64>(1);
+            let score = row.get::<_, i64>(2);
+            AuthorRes {
+                author: row.get(0),
+                editorials,
+                score,
+                up_votes: row.get(3),
+                down_votes: row.get(4),
+                average_score: score as f64 / editorials as f64,
+                contests: vec![],
+            }
+        }).collect::<Vec<_>>();
+
+        // breakdown by contest of the authors in the page
+        let authors = results.iter().map(|result| result.author.to_lowercase() ).collect::<Vec<_>>();
+        let author_index = authors.iter().enumerate().map(|(index, author)| (author.clone(), index) ).collect::<HashMap<_, _>>();
+        let rows = client.query(&format!("SELECT author_key, contest, COUNT(*), SUM(score)::BIGINT, SUM(up)::BIGINT, SUM(down)::BIGINT FROM ({editorial_scores}) AS editorial_scores WHERE author_key = ANY($4) GROUP BY author_key, contest ORDER BY author_key, contest NULLS LAST"), &[params[0], params[1], params[2], &authors])?;
+        for row in rows {
+            results[author_index[&row.get::<_, String>(0)]].contests.push(ContestRes {
+                contest: row.get(1),
+                editorials: row.get(2),
+                score: row.get(3),
+                up_votes: row.get(4),
+                down_votes: row.get(5),
+            });
+        }
+
+        Ok(Res {
+            status: "success",
+            results: Some(results),
+            next_offset,
+            .. Default::default()
+        })
+    }
+
+    Ok(database::with_database(use_db, (req, order, offset, limit)).await?)
+}
\ No newline at end of file
@@ -1,5 +1,4 @@
-use editorial_voting_vercel_serverless_function::atcoder_api;
-use serde;
+use editorial_voting_vercel_serverless_function::{atcoder_api, error, metrics};
 use vercel_runtime::{process_request, process_response, run_service, service_fn, Body, Error, Request, RequestPayloadExt, Response, ServiceBuilder, StatusCode};
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
@@ -11,6 +10,7 @@ struct Req {
 struct Res {
     status: &'static str,
     reason: Option<String>,
+    code: Option<&'static str>,
     affiliation_token: Option<String>,
 }
 
@@ -34,10 +34,13 @@ pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
             .header("Access-Control-Max-Age", "86400")
             .body(Body::Empty)?);
     }
+    let started = std::time::Instant::now();
     let res = match proc(req).await {
         Ok(affiliation_token) => Res { status: "success", affiliation_token: Some(affiliation_token), .. Default::default() },
-        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), .. Default::default() },
+        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), code: error::error_code(&*reason), .. Default::default() },
     };
+    metrics::record_request("create_affiliation_token", started, res.status, res.code);
+    metrics::flush().await;
     Ok(Response::builder()
         .status(StatusCode::OK)
         .header("Content-Type", "application/json")
@@ -1,5 +1,4 @@
-use editorial_voting_vercel_serverless_function::{atcoder_api, database};
-use serde;
+use editorial_voting_vercel_serverless_function::{atcoder_api, database, eligibility, error, metrics, moderation, user_rating};
 use vercel_runtime::{process_request, process_response, run_service, Body, Error, Request, RequestPayloadExt, Response, ServiceBuilder, StatusCode};
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
@@ -12,6 +11,7 @@ struct Req {
 struct Res {
     status: &'static str,
     reason: Option<String>,
+    code: Option<&'static str>,
     token: Option<String>,
 }
 
@@ -35,10 +35,13 @@ pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
             .header("Access-Control-Max-Age", "86400")
             .body(Body::Empty)?);
     }
+    let started = std::time::Instant::now();
     let res = match proc(req).await {
         Ok(token) => Res { status: "success", token: Some(token), .. Default::default() },
-        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), .. Default::default() },
+        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), code: error::error_code(&*reason), .. Default::default() },
     };
+    metrics::record_request("create_token", started, res.status, res.code);
+    metrics::flush().await;
     Ok(Response::builder()
         .status(StatusCode::OK)
         .header("Content-Type", "application/json")
@@ -68,11 +71,26 @@ async fn proc(req: Request) -> Result<String, Box<dyn std::error::Error>> {
 
     // connect database
     fn use_db(mut client: postgres::Client, atcoder_id: String) -> Result<i32, Box<dyn std::error::Error>> {
-        client.execute("INSERT INTO users(atcoder_id) VALUES($1) ON CONFLICT DO NOTHING", &[&atcoder_id])?;
+        if let Some(row) = client.query_opt("SELECT id FROM users WHERE atcoder_id = $1", &[&atcoder_id])? {
+            let user_id = row.get::<_, i32>(0);
+            moderation::ensure_not_banned(&mut client, user_id)?;
 
-        let row = client.query_one("SELECT id FROM users WHERE atcoder_id = $1", &[&atcoder_id])?;
+            // check eligibility rules
+            eligibility::check_eligibility(&mut client, user_id, &atcoder_id)?;
+            return Ok(user_id);
+        }
 
-        Ok(row.get::<_, i32>(0))
+        // check eligibility rules before registering, so that ineligible users are not left in the database
+        let details = eligibility::check_new_user(&atcoder_id)?;
+        client.execute("INSERT INTO users(atcoder_id, created_at) VALUES($1, $2) ON CONFLICT DO NOTHING", &[&atcoder_id, &std::time::SystemTime::now()])?;
+        let user_id = client.query_one("SELECT id FROM users WHERE atcoder_id = $1", &[&atcoder_id])?.get::<_, i32>(0);
+        // the row may have been created (or banned) since the check above
+        moderation::ensure_not_banned(&mut client, user_id)?;
+        if let Some(details) = details {
+            user_rating::store_rating(&mut client, user_id, &details)?;
+        }
+
+        Ok(user_id)
     }
     let user_id = database::with_database(use_db, req.atcoder_id.clone()).await?;
 
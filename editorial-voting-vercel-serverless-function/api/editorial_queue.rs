@@ -0,0 +1,84 @@
+use editorial_voting_vercel_serverless_function::{atcoder_api, database, editorials, error, metrics};
+use vercel_runtime::{process_request, process_response, run_service, Body, Error, Request, RequestPayloadExt, Response, ServiceBuilder, StatusCode};
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+struct Req {
+    contest: Option<String>,
+}
+
+#[derive(serde::Serialize, Default, Debug)]
+struct Res {
+    status: &'static str,
+    reason: Option<String>,
+    code: Option<&'static str>,
+    results: Option<Vec<PendingRes>>,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct PendingRes {
+    editorial: String,
+    contest: Option<String>,
+    task: Option<String>,
+    endorsements: i64,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let handler = ServiceBuilder::new()
+        .map_request(process_request)
+        .map_response(process_response)
+        .service_fn(handler);
+
+    run_service(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    if req.method() == "OPTIONS" {
+        return Ok(Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .header("Access-Control-Allow-Origin", "*")
+            .header("Access-Control-Allow-Methods", "*")
+            .header("Access-Control-Allow-Headers", "*")
+            .header("Access-Control-Max-Age", "86400")
+            .body(Body::Empty)?);
+    }
+    let started = std::time::Instant::now();
+    let res = match proc(req).await {
+        Ok(res) => res,
+        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), code: error::error_code(&*reason), .. Default::default() },
+    };
+    metrics::record_request("editorial_queue", started, res.status, res.code);
+    metrics::flush().await;
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/json")
+        .header("Access-Control-Allow-Origin", "*")
+        .header("Access-Control-Allow-Headers", "*")
+        .body(Body::Text(serde_json::to_string(&res)?))?)
+}
+
+async fn proc(req: Request) -> Result<Res, Box<dyn std::error::Error>> {
+    let Ok(Some(req)) = req.payload::<Req>() else {
+        return Err("invalid request".into());
+    };
+    if req.contest.as_deref().is_some_and(|contest| !atcoder_api::validate_contest(contest) ) {
+        return Err("contest invalid format".into());
+    }
+
+    fn use_db(mut client: postgres::Client, req: Req) -> Result<Res, Box<dyn std::error::Error>> {
+        let results = editorials::pending_editorials(&mut client, req.contest.as_deref())?.into_iter().map(|pending| PendingRes {
+            editorial: pending.editorial,
+            contest: pending.contest,
+            task: pending.task,
+            endorsements: pending.endorsements,
+        }).collect();
+
+        Ok(Res {
+            status: "success",
+            results: Some(results),
+            .. Default::default()
+        })
+    }
+
+    Ok(database::with_database(use_db, req).await?)
+}
\ No newline at end of file
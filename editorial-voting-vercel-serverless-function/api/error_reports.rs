@@ -0,0 +1,93 @@
+use editorial_voting_vercel_serverless_function::{auth, database, error, error_reports, metrics};
+use vercel_runtime::{process_request, process_response, run_service, Body, Error, Request, RequestPayloadExt, Response, ServiceBuilder, StatusCode};
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+struct Req {
+    token: String,
+    editorial: Option<String>,
+    limit: Option<i64>,
+}
+
+#[derive(serde::Serialize, Default, Debug)]
+struct Res {
+    status: &'static str,
+    reason: Option<String>,
+    code: Option<&'static str>,
+    reports: Option<Vec<ReportRes>>,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct ReportRes {
+    editorial: String,
+    reporter: Option<String>,
+    description: String,
+    counterexample: Option<String>,
+    created_at: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let handler = ServiceBuilder::new()
+        .map_request(process_request)
+        .map_response(process_response)
+        .service_fn(handler);
+
+    run_service(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    if req.method() == "OPTIONS" {
+        return Ok(Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .header("Access-Control-Allow-Origin", "*")
+            .header("Access-Control-Allow-Methods", "*")
+            .header("Access-Control-Allow-Headers", "*")
+            .header("Access-Control-Max-Age", "86400")
+            .body(Body::Empty)?);
+    }
+    let started = std::time::Instant::now();
+    let res = match proc(req).await {
+        Ok(res) => res,
+        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), code: error::error_code(&*reason), .. Default::default() },
+    };
+    metrics::record_request("error_reports", started, res.status, res.code);
+    metrics::flush().await;
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/json")
+        .header("Access-Control-Allow-Origin", "*")
+        .header("Access-Control-Allow-Headers", "*")
+        .body(Body::Text(serde_json::to_string(&res)?))?)
+}
+
+async fn proc(req: Request) -> Result<Res, Box<dyn std::error::Error>> {
+    let Ok(Some(req)) = req.payload::<Req>() else {
+        return Err("invalid request".into());
+    };
+    let limit = req.limit.unwrap_or(50);
+    if !(1 ..= 200).contains(&limit) {
+        return Err("limit must be between 1 and 200".into());
+    }
+
+    fn use_db(mut client: postgres::Client, (req, limit): (Req, i64)) -> Result<Res, Box<dyn std::error::Error>> {
+        let user_token = auth::verify_token(&mut client, &req.token)?;
+
+        let reports = error_reports::list_error_reports(&mut client, user_token.user_id, &user_token.atcoder_id, req.editorial.as_deref(), limit)?.into_iter().map(|report| -> Result<ReportRes, Box<dyn std::error::Error>> {
+            Ok(ReportRes {
+                editorial: report.editorial,
+                reporter: report.reporter,
+                description: report.description,
+                counterexample: report.counterexample,
+                created_at: report.created_at.duration_since(std::time::UNIX_EPOCH)?.as_secs(),
+            })
+        }).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Res {
+            status: "success",
+            reports: Some(reports),
+            .. Default::default()
+        })
+    }
+
+    Ok(database::with_database(use_db, (req, limit)).await?)
+}
\ No newline at end of file
@@ -0,0 +1,31 @@
+use editorial_voting_vercel_serverless_function::{database, metrics};
+use vercel_runtime::{process_request, process_response, run_service, Body, Error, Request, Response, ServiceBuilder, StatusCode};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let handler = ServiceBuilder::new()
+        .map_request(process_request)
+        .map_response(process_response)
+        .service_fn(handler);
+
+    run_service(handler).await
+}
+
+pub async fn handler(_req: Request) -> Result<Response<Body>, Error> {
+    // save the samples of this instance first so that they are included
+    metrics::flush().await;
+
+    fn use_db(mut client: postgres::Client, _: ()) -> Result<Vec<metrics::Sample>, Box<dyn std::error::Error>> {
+        metrics::load_samples(&mut client)
+    }
+    match database::with_database(use_db, ()).await {
+        Ok(samples) => Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "text/plain; version=0.0.4")
+            .body(Body::Text(metrics::render_samples(&samples)))?),
+        Err(reason) => Ok(Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .header("Content-Type", "text/plain")
+            .body(Body::Text(reason.to_string()))?),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,145 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use editorial_voting_vercel_serverless_function::{atcoder_api, auth, database, editorials, error, metrics};
+use vercel_runtime::{process_request, process_response, run_service, Body, Error, Request, RequestPayloadExt, Response, ServiceBuilder, StatusCode};
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+struct Req {
+    token: String,
+    contest: Option<String>,
+    vote: Option<String>,
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+#[derive(serde::Serialize, Default, Debug)]
+struct Res {
+    status: &'static str,
+    reason: Option<String>,
+    code: Option<&'static str>,
+    votes: Option<Vec<VoteRes>>,
+    next_cursor: Option<String>,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct VoteRes {
+    editorial: String,
+    contest: Option<String>,
+    task: Option<String>,
+    vote: &'static str,
+    voted_at: Option<u64>,
+    rating: Option<i16>,
+    hidden: bool,
+}
+
+/// position after the last vote of the page: time of the vote (microseconds, 0 if unknown) and editorial id
+struct Cursor {
+    voted_at: SystemTime,
+    editorial_id: i32,
+}
+
+impl Cursor {
+    fn parse(cursor: &str) -> Option<Cursor> {
+        let (micros, editorial_id) = cursor.split_once(':')?;
+        Some(Cursor {
+            voted_at: UNIX_EPOCH.checked_add(Duration::from_micros(micros.parse().ok()?))?,
+            editorial_id: editorial_id.parse().ok()?,
+        })
+    }
+
+    fn encode(&self) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(format!("{}:{}", self.voted_at.duration_since(UNIX_EPOCH)?.as_micros(), self.editorial_id))
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let handler = ServiceBuilder::new()
+        .map_request(process_request)
+        .map_response(process_response)
+        .service_fn(handler);
+
+    run_service(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    if req.method() == "OPTIONS" {
+        return Ok(Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .header("Access-Control-Allow-Origin", "*")
+            .header("Access-Control-Allow-Methods", "*")
+            .header("Access-Control-Allow-Headers", "*")
+            .header("Access-Control-Max-Age", "86400")
+            .body(Body::Empty)?);
+    }
+    let started = std::time::Instant::now();
+    let res = match proc(req).await {
+        Ok(res) => res,
+        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), code: error::error_code(&*reason), .. Default::default() },
+    };
+    metrics::record_request("my_votes", started, res.status, res.code);
+    metrics::flush().await;
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/json")
+        .header("Access-Control-Allow-Origin", "*")
+        .header("Access-Control-Allow-Headers", "*")
+        .body(Body::Text(serde_json::to_string(&res)?))?)
+}
+
+async fn proc(req: Request) -> Result<Res, Box<dyn std::error::Error>> {
+    let Ok(Some(req)) = req.payload::<Req>() else {
+        return Err("invalid request".into());
+    };
+
+    if req.contest.as_deref().is_some_and(|contest| !atcoder_api::validate_contest(contest) ) {
+        return Err("contest invalid format".into());
+    }
+    let score = match req.vote.as_deref() {
+        None => None,
+        Some("up") => Some(1i16),
+        Some("down") => Some(-1i16),
+        Some(_) => return Err("invalid vote format (up|down)".into()),
+    };
+    let cursor = req.cursor.as_deref().map(|cursor| Cursor::parse(cursor).ok_or("invalid cursor") ).transpose()?;
+    let limit = req.limit.unwrap_or(50);
+    if !(1 ..= 200).contains(&limit) {
+        return Err("limit must be between 1 and 200".into());
+    }
+
+    fn use_db(mut client: postgres::Client, (req, score, cursor, limit): (Req, Option<i16>, Option<Cursor>, i64)) -> Result<Res, Box<dyn std::error::Error>> {
+        let user_token = auth::verify_token(&mut client, &req.token)?;
+
+        // newest first (votes before voted_at was recorded are last)
+        let (cursor_time, cursor_id) = match cursor.as_ref() {
+            Some(cursor) => (Some(cursor.voted_at), Some(cursor.editorial_id)),
+            None => (None, None),
+        };
+        let rows = client.query(&format!("SELECT editorials.id, editorial, contest, task, votes.score, votes.voted_at, votes.rating, COALESCE(votes.voted_at, TIMESTAMP 'epoch') AS sort_time, {} AS visible FROM votes JOIN editorials ON editorials.id = votes.editorial_id WHERE votes.user_id = $1 AND ($2::TEXT IS NULL OR contest = $2) AND ($3::SMALLINT IS NULL OR votes.score = $3) AND ($4::TIMESTAMP IS NULL OR (COALESCE(votes.voted_at, TIMESTAMP 'epoch'), editorials.id) < ($4, $5)) ORDER BY sort_time DESC, editorials.id DESC LIMIT $6", editorials::VISIBLE_SQL), &[&user_token.user_id, &req.contest, &score, &cursor_time, &cursor_id, &(limit + 1)])?;
+
+        let next_cursor = match rows.get(limit as usize - 1) {
+            Some(last) if rows.len() as i64 > limit => Some(Cursor { voted_at: last.get::<_, SystemTime>(7), editorial_id: last.get::<_, i32>(0) }.encode()?),
+            _ => None,
+        };
+        let votes = rows.iter().take(limit as usize).map(|row| -> Result<VoteRes, Box<dyn std::error::Error>> {
+            Ok(VoteRes {
+                editorial: row.get(1),
+                contest: row.get(2),
+                task: row.get(3),
+                vote: if row.get::<_, i16>(4) > 0 { "up" } else { "down" },
+                voted_at: row.get::<_, Option<SystemTime>>(5).map(|time| time.duration_since(UNIX_EPOCH) ).transpose()?.map(|duration| duration.as_secs() ),
+                rating: row.get(6),
+                hidden: !row.get::<_, bool>(8),
+            })
+        }).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Res {
+            status: "success",
+            votes: Some(votes),
+            next_cursor,
+            .. Default::default()
+        })
+    }
+
+    Ok(database::with_database(use_db, (req, score, cursor, limit)).await?)
+}
\ No newline at end of file
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use editorial_voting_vercel_serverless_function::{atcoder_api, auth, contests, database, editorials, error::{self, ApiError}, metrics, ranking::VoteCounts, rating_bucket, user_rating};
+use vercel_runtime::{process_request, process_response, run_service, Body, Error, Request, RequestPayloadExt, Response, ServiceBuilder, StatusCode};
+
+/// default half width of the rating neighborhood
+const DEFAULT_WIDTH: i16 = 200;
+/// if the neighborhood has fewer votes than this, global votes are used instead
+const DEFAULT_MIN_VOTES: i64 = 3;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+struct Req {
+    token: Option<String>,
+    rating: Option<i16>,
+    contest: String,
+    task: Option<String>,
+    width: Option<i16>,
+    min_votes: Option<i64>,
+}
+
+#[derive(serde::Serialize, Default, Debug)]
+struct Res {
+    status: &'static str,
+    reason: Option<String>,
+    code: Option<&'static str>,
+    rating: Option<i16>,
+    results: Option<Vec<Recommendation>>,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct Recommendation {
+    editorial: String,
+    task: Option<String>,
+    basis: &'static str,
+    score: i64,
+    up_votes: i64,
+    down_votes: i64,
+    wilson_score: f64,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let handler = ServiceBuilder::new()
+        .map_request(process_request)
+        .map_response(process_response)
+        .service_fn(handler);
+
+    run_service(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    if req.method() == "OPTIONS" {
+        return Ok(Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .header("Access-Control-Allow-Origin", "*")
+            .header("Access-Control-Allow-Methods", "*")
+            .header("Access-Control-Allow-Headers", "*")
+            .header("Access-Control-Max-Age", "86400")
+            .body(Body::Empty)?);
+    }
+    let started = std::time::Instant::now();
+    let res = match proc(req).await {
+        Ok(res) => res,
+        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), code: error::error_code(&*reason), .. Default::default() },
+    };
+    metrics::record_request("recommend", started, res.status, res.code);
+    metrics::flush().await;
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/json")
+        .header("Access-Control-Allow-Origin", "*")
+        .header("Access-Control-Allow-Headers", "*")
+        .body(Body::Text(serde_json::to_string(&res)?))?)
+}
+
+async fn proc(req: Request) -> Result<Res, Box<dyn std::error::Error>> {
+    let Ok(Some(req)) = req.payload::<Req>() else {
+        return Err("invalid request".into());
+    };
+
+    if !atcoder_api::validate_contest(&req.contest) {
+        return Err("contest invalid format".into());
+    }
+    if let Some(rating) = req.rating {
+        if !(0 ..= 9999).contains(&rating) {
+            return Err("rating must be between 0 and 9999".into());
+        }
+    }
+    let width = req.width.unwrap_or(DEFAULT_WIDTH);
+    if !(0 ..= 2000).contains(&width) {
+        return Err("width must be between 0 and 2000".into());
+    }
+    let min_votes = req.min_votes.unwrap_or(DEFAULT_MIN_VOTES);
+
+    fn use_db(mut client: postgres::Client, (req, width, min_votes): (Req, i16, i64)) -> Result<Res, Box<dyn std::error::Error>> {
+        // get rating
+        let rating = if let Some(rating) = req.rating {
+            Some(rating)
+        } else if let Some(token) = req.token.as_ref() {
+            let user_token = auth::verify_token(&mut client, token)?;
+            let heuristic = contests::cached_contest(&mut client, &req.contest)?.is_some_and(|contest| contest.heuristic );
+            user_rating::get_rating(&mut client, user_token.user_id, &user_token.atcoder_id, heuristic)?
+        } else {
+            return Err("token or rating is required".into());
+        };
+        // the neighborhood of unrated users is the unrated voters
+        let (min_level, max_level) = match rating {
+            Some(rating) => ((rating - width).max(0) / 100, (rating + width) / 100),
+            None => (rating_bucket::UNRATED_LEVEL, rating_bucket::UNRATED_LEVEL),
+        };
+
+        // コンテスト中はスコアを隠す
+        if contests::hides_scores(&mut client, &req.contest)? {
+            return Err(ApiError::new(contests::CONTEST_RUNNING, "not available until the contest ends").into());
+        }
+
+        // get votes by rating level (editorials of contests nobody has voted on are not registered yet, so the result is empty)
+        let mut editorials = vec![];
+        let mut editorial_index = HashMap::new();
+        let rows = client.query(&format!("SELECT editorials.id, editorial, task, rating_level, up, down FROM editorials LEFT JOIN vote_temp ON vote_temp.editorial_id = editorials.id WHERE contest = $1 AND ($2::TEXT IS NULL OR task = $2) AND {}", editorials::VISIBLE_SQL), &[&req.contest, &req.task])?;
+        for row in rows {
+            let id = row.get::<_, i32>(0);
+            let index = *editorial_index.entry(id).or_insert_with(|| {
+                editorials.push((row.get::<_, String>(1), row.get::<_, Option<String>>(2), VoteCounts::default(), VoteCounts::default()));
+                editorials.len() - 1
+            });
+            let Some(rating_level) = row.get::<_, Option<i16>>(3) else {
+                // no votes
+                continue;
+            };
+            let counts = VoteCounts::new(row.get::<_, i32>(4) as i64, row.get::<_, i32>(5) as i64);
+            let (_, _, local, global) = &mut editorials[index];
+            global.add(counts);
+            if (min_level ..= max_level).contains(&rating_level) {
+                local.add(counts);
+            }
+        }
+
+        let mut results = editorials.into_iter().map(|(editorial, task, local, global)| {
+            let (basis, counts) = if local.total() >= min_votes { ("local", local) } else { ("global", global) };
+            Recommendation {
+                editorial,
+                task,
+                basis,
+                score: counts.score(),
+                up_votes: counts.up,
+                down_votes: counts.down,
+                wilson_score: counts.wilson_lower_bound(),
+            }
+        }).collect::<Vec<_>>();
+        results.sort_by(|a, b| b.wilson_score.total_cmp(&a.wilson_score).then(b.score.cmp(&a.score)).then_with(|| a.editorial.cmp(&b.editorial) ) );
+
+        Ok(Res {
+            status: "success",
+            rating,
+            results: Some(results),
+            .. Default::default()
+        })
+    }
+
+    Ok(database::with_database(use_db, (req, width, min_votes)).await?)
+}
@@ -1,22 +1,39 @@
 use std::collections::HashMap;
 
-use editorial_voting_vercel_serverless_function::{atcoder_api, database};
-use serde;
+use editorial_voting_vercel_serverless_function::{aggregate::{self, Aggregate}, atcoder_api, auth, config, contests, criteria::{self, CriterionSummary}, database, editorials, error, metrics, ranking::CompositeOptions, rating_bucket::{BucketScheme, BucketsParam}};
 use vercel_runtime::{process_request, process_response, run_service, Body, Error, Request, RequestPayloadExt, Response, ServiceBuilder, StatusCode};
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 struct Req {
     token: Option<String>,
     editorial: String,
+    buckets: Option<BucketsParam>,
+    composite: Option<CompositeOptions>,
 }
 
 #[derive(serde::Serialize, Default, Debug)]
 struct Res {
     status: &'static str,
     reason: Option<String>,
+    code: Option<&'static str>,
+    rating_policy: Option<&'static str>,
     score: Option<i64>,
     scores_by_rating: Option<HashMap<String, i64>>,
     current_vote: Option<&'static str>,
+    up_votes: Option<i64>,
+    down_votes: Option<i64>,
+    wilson_score: Option<f64>,
+    bayesian_score: Option<f64>,
+    composite_score: Option<f64>,
+    solver_score: Option<i64>,
+    solver_up_votes: Option<i64>,
+    solver_down_votes: Option<i64>,
+    tag_counts: Option<HashMap<String, i64>>,
+    error_reports: Option<i64>,
+    criteria: Option<HashMap<String, CriterionSummary>>,
+    criteria_by_rating: Option<HashMap<String, HashMap<String, CriterionSummary>>>,
+    author: Option<String>,
+    scores_hidden: Option<bool>,
 }
 
 #[tokio::main]
@@ -39,10 +56,13 @@ pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
             .header("Access-Control-Max-Age", "86400")
             .body(Body::Empty)?);
     }
+    let started = std::time::Instant::now();
     let res = match proc(req).await {
         Ok(res) => res,
-        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), .. Default::default() },
+        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), code: error::error_code(&*reason), .. Default::default() },
     };
+    metrics::record_request("status", started, res.status, res.code);
+    metrics::flush().await;
     Ok(Response::builder()
         .status(StatusCode::OK)
         .header("Content-Type", "application/json")
@@ -56,57 +76,75 @@ async fn proc(req: Request) -> Result<Res, Box<dyn std::error::Error>> {
         return Err("invalid request".into());
     };
 
-    fn use_db(mut client: postgres::Client, req: Req) -> Result<Res, Box<dyn std::error::Error>> {
+    let scheme = BucketScheme::from_param(req.buckets.as_ref())?;
+    if let Some(composite) = req.composite.as_ref() {
+        composite.validate()?;
+    }
+
+    fn use_db(mut client: postgres::Client, (req, scheme): (Req, BucketScheme)) -> Result<Res, Box<dyn std::error::Error>> {
         let mut user_token = None;
         if let Some(token) = req.token.as_ref() {
-            user_token = Some(atcoder_api::parse_token(token)?);
+            user_token = Some(auth::verify_token(&mut client, token)?);
         }
 
         // get editorial_id
         let Some(editorial_url) = atcoder_api::canonicalize_editorial_url(&req.editorial) else {
             return Err("invalid editorial URL".into());
         };
-        
-        let Some(row) = client.query_opt("SELECT id FROM editorials WHERE editorial = $1", &[&editorial_url])? else {
-            // 未登録
-            return Ok(Res {
-                status: "success",
-                score: Some(0),
-                scores_by_rating: Some(HashMap::new()),
-                current_vote: user_token.as_ref().map(|_| "none" ),
-                .. Default::default()
-            });
-        };
-        let editorial_id = row.get::<_, i32>(0);
-        
-        // get score
-        let mut score = 0;
-        let mut scores_by_rating = HashMap::new();
-        let rows = client.query("SELECT rating_level, score FROM vote_temp WHERE editorial_id = $1", &[&editorial_id])?;
-        for row in rows {
-            let rating_level = row.get::<_, i16>(0) as usize;
-            let score_by_rating_level = row.get::<_, i32>(1) as i64;
-            score += score_by_rating_level;
-            scores_by_rating.insert(format!("{}-{}", rating_level * 100, rating_level * 100 + 99), score_by_rating_level);
+        // 未登録 (または非表示、承認待ち) なら None
+        let editorial = client.query_opt(&format!("SELECT id, author, contest FROM editorials WHERE editorial = $1 AND {}", editorials::VISIBLE_SQL), &[&editorial_url])?;
+        let editorial_id = editorial.as_ref().map(|row| row.get::<_, i32>(0) );
+        let author = editorial.as_ref().and_then(|row| row.get::<_, Option<String>>(1) );
+
+        // コンテスト中はスコアを隠す
+        let mut scores_hidden = false;
+        if let Some(contest) = editorial.as_ref().and_then(|row| row.get::<_, Option<String>>(2) ) {
+            scores_hidden = contests::hides_scores(&mut client, &contest)?;
         }
 
+        // get score
+        let aggregate = match editorial_id {
+            Some(editorial_id) if !scores_hidden => aggregate::aggregate_editorials(&mut client, &[editorial_id], &scheme)?.remove(&editorial_id).unwrap_or_default(),
+            _ => Aggregate::default(),
+        };
+
         let mut current_vote = None;
         if let Some(user_token) = user_token.as_ref() {
-            current_vote = Some(match client.query_opt("SELECT score FROM votes WHERE user_id = $1 AND editorial_id = $2", &[&user_token.user_id, &editorial_id])?.map(|row| row.get::<_, i16>(0) ) {
+            let vote = match editorial_id {
+                Some(editorial_id) => client.query_opt("SELECT score FROM votes WHERE user_id = $1 AND editorial_id = $2", &[&user_token.user_id, &editorial_id])?.map(|row| row.get::<_, i16>(0) ),
+                None => None,
+            };
+            current_vote = Some(match vote {
                 Some(1) => "up",
                 Some(-1) => "down",
                 _ => "none"
             });
         }
-    
+
+        let counts = aggregate.counts;
         Ok(Res {
             status: "success",
-            score: Some(score),
-            scores_by_rating: Some(scores_by_rating),
+            rating_policy: Some(config::rating_policy()?.as_str()),
+            score: Some(counts.score()),
+            scores_by_rating: Some(aggregate.scores_by_rating()),
             current_vote,
+            up_votes: Some(counts.up),
+            down_votes: Some(counts.down),
+            wilson_score: Some(counts.wilson_lower_bound()),
+            bayesian_score: Some(counts.bayesian_average()),
+            composite_score: req.composite.as_ref().and_then(|composite| aggregate.composite_score(composite) ),
+            solver_score: Some(aggregate.solver_counts.score()),
+            solver_up_votes: Some(aggregate.solver_counts.up),
+            solver_down_votes: Some(aggregate.solver_counts.down),
+            tag_counts: Some(aggregate.tag_counts.clone()),
+            error_reports: Some(aggregate.error_reports),
+            criteria: Some(criteria::summaries(&aggregate.criteria)),
+            criteria_by_rating: Some(aggregate.criteria_by_rating()),
+            author,
+            scores_hidden: Some(scores_hidden),
             .. Default::default()
         })
     }
 
-    Ok(database::with_database(use_db, req).await?)
+    Ok(database::with_database(use_db, (req, scheme)).await?)
 }
\ No newline at end of file
@@ -1,19 +1,22 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use editorial_voting_vercel_serverless_function::{atcoder_api, database};
-use serde;
+use editorial_voting_vercel_serverless_function::{aggregate::{self, Aggregate}, auth, config, contests, criteria::{self, CriterionSummary}, database, editorials, error, metrics, ranking::CompositeOptions, rating_bucket::{BucketScheme, BucketsParam}};
 use vercel_runtime::{process_request, process_response, run_service, Body, Error, Request, RequestPayloadExt, Response, ServiceBuilder, StatusCode};
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 struct Req {
     token: Option<String>,
     editorials: Vec<String>,
+    buckets: Option<BucketsParam>,
+    composite: Option<CompositeOptions>,
 }
 
 #[derive(serde::Serialize, Default, Debug)]
 struct Res {
     status: &'static str,
     reason: Option<String>,
+    code: Option<&'static str>,
+    rating_policy: Option<&'static str>,
     results: Option<Vec<SingleRes>>,
 }
 
@@ -22,6 +25,45 @@ struct SingleRes {
     score: i64,
     scores_by_rating: HashMap<String, i64>,
     current_vote: Option<&'static str>,
+    up_votes: i64,
+    down_votes: i64,
+    wilson_score: f64,
+    bayesian_score: f64,
+    composite_score: Option<f64>,
+    solver_score: i64,
+    solver_up_votes: i64,
+    solver_down_votes: i64,
+    tag_counts: HashMap<String, i64>,
+    error_reports: i64,
+    criteria: HashMap<String, CriterionSummary>,
+    criteria_by_rating: HashMap<String, HashMap<String, CriterionSummary>>,
+    author: Option<String>,
+    scores_hidden: bool,
+}
+
+impl SingleRes {
+    fn new(aggregate: &Aggregate, current_vote: Option<&'static str>, composite: Option<&CompositeOptions>) -> Self {
+        let counts = aggregate.counts;
+        SingleRes {
+            score: counts.score(),
+            scores_by_rating: aggregate.scores_by_rating(),
+            current_vote,
+            up_votes: counts.up,
+            down_votes: counts.down,
+            wilson_score: counts.wilson_lower_bound(),
+            bayesian_score: counts.bayesian_average(),
+            composite_score: composite.and_then(|composite| aggregate.composite_score(composite) ),
+            solver_score: aggregate.solver_counts.score(),
+            solver_up_votes: aggregate.solver_counts.up,
+            solver_down_votes: aggregate.solver_counts.down,
+            tag_counts: aggregate.tag_counts.clone(),
+            error_reports: aggregate.error_reports,
+            criteria: criteria::summaries(&aggregate.criteria),
+            criteria_by_rating: aggregate.criteria_by_rating(),
+            author: None,
+            scores_hidden: false,
+        }
+    }
 }
 
 #[derive(serde::Serialize, Default, Debug)]
@@ -30,11 +72,6 @@ struct EditorialUrlQuery {
     query: String,
 }
 
-#[derive(serde::Serialize, Default, Debug)]
-struct IdQuery {
-    id: i32,
-}
-
 #[tokio::main]
 async fn main() -> Result<(), Error> {
     let handler = ServiceBuilder::new()
@@ -55,10 +92,13 @@ pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
             .header("Access-Control-Max-Age", "86400")
             .body(Body::Empty)?);
     }
+    let started = std::time::Instant::now();
     let res = match proc(req).await {
         Ok(res) => res,
-        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), .. Default::default() },
+        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), code: error::error_code(&*reason), .. Default::default() },
     };
+    metrics::record_request("statuses", started, res.status, res.code);
+    metrics::flush().await;
     Ok(Response::builder()
         .status(StatusCode::OK)
         .header("Content-Type", "application/json")
@@ -76,93 +116,80 @@ async fn proc(req: Request) -> Result<Res, Box<dyn std::error::Error>> {
         return Err("number of editorials must be less than or equal to 256".into());
     }
 
-    fn use_db(mut client: postgres::Client, req: Req) -> Result<Res, Box<dyn std::error::Error>> {
+    let scheme = BucketScheme::from_param(req.buckets.as_ref())?;
+    if let Some(composite) = req.composite.as_ref() {
+        composite.validate()?;
+    }
+
+    fn use_db(mut client: postgres::Client, (req, scheme): (Req, BucketScheme)) -> Result<Res, Box<dyn std::error::Error>> {
+        let mut user_token = None;
         if let Some(token) = req.token.as_ref() {
-            let user_token = atcoder_api::parse_token(token)?;
-
-            // get editorial_ids
-            let (editorial_ids, editorial_id_map) = {
-                let query_records = req.editorials.iter().enumerate().map(|(index, query)| EditorialUrlQuery { index: index as i32, query: query.to_string() } ).collect::<Vec<_>>();
-                let json = serde_json::to_value(&query_records)?;
-                let rows = client.query("SELECT id, index FROM editorials, JSON_TO_RECORDSET($1) AS queries(index INTEGER, query TEXT) WHERE editorial = query", &[&json])?;
-                // return (id, index)
-                let mut editorial_ids = vec![];
-                let mut editorial_id_map = HashMap::new();
-                for row in rows {
-                    let id = row.get::<_, i32>(0);
-                    let index = row.get::<_, i32>(1) as usize;
-                    editorial_ids.push(id);
-                    editorial_id_map.insert(id, index);
+            user_token = Some(auth::verify_token(&mut client, token)?);
+        }
+
+        // get editorial_ids (None if not registered, hidden or pending)
+        let mut editorial_ids = vec![None; req.editorials.len()];
+        let mut authors = HashMap::new();
+        let mut editorial_contests = HashMap::new();
+        {
+            let query_records = req.editorials.iter().enumerate().map(|(index, query)| EditorialUrlQuery { index: index as i32, query: query.to_string() } ).collect::<Vec<_>>();
+            let json = serde_json::to_value(&query_records)?;
+            let rows = client.query(&format!("SELECT id, index, author, contest FROM editorials, JSON_TO_RECORDSET($1) AS queries(index INTEGER, query TEXT) WHERE editorial = query AND {}", editorials::VISIBLE_SQL), &[&json])?;
+            for row in rows {
+                let id = row.get::<_, i32>(0);
+                let index = row.get::<_, i32>(1) as usize;
+                editorial_ids[index] = Some(id);
+                if let Some(author) = row.get::<_, Option<String>>(2) {
+                    authors.insert(id, author);
                 }
-                (editorial_ids, editorial_id_map)
-            };
-
-            let mut results = vec![SingleRes {
-                current_vote: Some("none"),
-                ..Default::default()
-            }; req.editorials.len()];
-
-            let editorial_ids_json = serde_json::to_value(&editorial_ids.iter().map(|&id| IdQuery { id } ).collect::<Vec<_>>())?;
-
-            // get scores
-            {
-                let rows = client.query("SELECT id, rating_level, score FROM vote_temp, JSON_TO_RECORDSET($1) AS queries(id INTEGER) WHERE editorial_id = queries.id", &[&editorial_ids_json])?;
-                for row in rows {
-                    let id = row.get::<_, i32>(0);
-                    let rating_level = row.get::<_, i16>(1) as usize;
-                    let score_by_rating_level = row.get::<_, i32>(2) as i64;
-                    let index = editorial_id_map[&id];
-                    results[index].score += score_by_rating_level;
-                    results[index].scores_by_rating.insert(format!("{}-{}", rating_level * 100, rating_level * 100 + 99), score_by_rating_level);
+                if let Some(contest) = row.get::<_, Option<String>>(3) {
+                    editorial_contests.insert(id, contest);
                 }
             }
+        }
 
-            // get current votes
-            {
-                let rows = client.query("SELECT id, score FROM votes, JSON_TO_RECORDSET($1) AS queries(id INTEGER) WHERE user_id = $2 AND editorial_id = queries.id", &[&editorial_ids_json, &user_token.user_id])?;
-                for row in rows {
-                    let id = row.get::<_, i32>(0);
-                    let score = row.get::<_, i16>(1);
-                    let index = editorial_id_map[&id];
-                    match score {
-                        1 => {
-                            results[index].current_vote = Some("up");
-                        }
-                        -1 => {
-                            results[index].current_vote = Some("down");
-                        }
-                        _ => {}
-                    }
-                }
+        // コンテスト中はスコアを隠す
+        let mut hidden_contests = HashSet::new();
+        for contest in editorial_contests.values().collect::<HashSet<_>>() {
+            if contests::hides_scores(&mut client, contest)? {
+                hidden_contests.insert(contest.clone());
             }
+        }
+        let scores_hidden = |id: i32| editorial_contests.get(&id).is_some_and(|contest| hidden_contests.contains(contest) );
+        let registered_ids = editorial_ids.iter().flatten().copied().filter(|&id| !scores_hidden(id) ).collect::<Vec<_>>();
 
-            Ok(Res {
-                status: "success",
-                results: Some(results),
-                .. Default::default()
-            })
-        } else {
-            let mut results = vec![SingleRes::default(); req.editorials.len()];
+        // get scores
+        let aggregates = aggregate::aggregate_editorials(&mut client, &registered_ids, &scheme)?;
 
-            // get scores
-            let query_records = req.editorials.iter().enumerate().map(|(index, query)| EditorialUrlQuery { index: index as i32, query: query.to_string() } ).collect::<Vec<_>>();
-            let json = serde_json::to_value(&query_records)?;
-            let rows = client.query("SELECT index, rating_level, score FROM editorials, vote_temp, JSON_TO_RECORDSET($1) AS queries(index INTEGER, query TEXT) WHERE editorial_id = id AND editorial = query", &[&json])?;
+        // get current votes
+        let mut current_votes = HashMap::new();
+        if let Some(user_token) = user_token.as_ref() {
+            let rows = client.query("SELECT editorial_id, score FROM votes WHERE user_id = $1 AND editorial_id = ANY($2)", &[&user_token.user_id, &registered_ids])?;
             for row in rows {
-                let index = row.get::<_, i32>(0) as usize;
-                let rating_level = row.get::<_, i16>(1) as usize;
-                let score_by_rating_level = row.get::<_, i32>(2) as i64;
-                results[index].score += score_by_rating_level;
-                results[index].scores_by_rating.insert(format!("{}-{}", rating_level * 100, rating_level * 100 + 99), score_by_rating_level);
+                current_votes.insert(row.get::<_, i32>(0), row.get::<_, i16>(1));
             }
-
-            Ok(Res {
-                status: "success",
-                results: Some(results),
-                .. Default::default()
-            })
         }
+
+        let results = editorial_ids.iter().map(|&editorial_id| {
+            let aggregate = editorial_id.and_then(|id| aggregates.get(&id) ).cloned().unwrap_or_default();
+            let current_vote = user_token.as_ref().map(|_| match editorial_id.and_then(|id| current_votes.get(&id) ) {
+                Some(1) => "up",
+                Some(-1) => "down",
+                _ => "none",
+            });
+            let mut result = SingleRes::new(&aggregate, current_vote, req.composite.as_ref());
+            result.author = editorial_id.and_then(|id| authors.get(&id) ).cloned();
+            result.scores_hidden = editorial_id.is_some_and(scores_hidden);
+            result
+        }).collect();
+
+        Ok(Res {
+            status: "success",
+            rating_policy: Some(config::rating_policy()?.as_str()),
+            results: Some(results),
+            .. Default::default()
+        })
     }
 
-    Ok(database::with_database(use_db, req).await?)
+    Ok(database::with_database(use_db, (req, scheme)).await?)
 }
\ No newline at end of file
@@ -1,7 +1,6 @@
-use std::time::{Duration, SystemTime};
+use std::collections::HashMap;
 
-use editorial_voting_vercel_serverless_function::{atcoder_api, database};
-use serde;
+use editorial_voting_vercel_serverless_function::{aggregate, atcoder_api, auth, config::{self, SelfVotePolicy, UnratedPolicy}, contests, criteria, database, editorials, eligibility, error::{self, ApiError}, metrics, moderation, rating_bucket, user_rating, vote_tags};
 use vercel_runtime::{process_request, process_response, run_service, Body, Error, Request, RequestPayloadExt, Response, ServiceBuilder, StatusCode};
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
@@ -10,12 +9,15 @@ struct Req {
     contest: String,
     editorial: String,
     vote: String,
+    tags: Option<Vec<String>>,
+    criteria: Option<HashMap<String, i16>>,
 }
 
 #[derive(serde::Serialize, Default, Debug)]
 struct Res {
     status: &'static str,
     reason: Option<String>,
+    code: Option<&'static str>,
 }
 
 #[tokio::main]
@@ -38,10 +40,13 @@ pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
             .header("Access-Control-Max-Age", "86400")
             .body(Body::Empty)?);
     }
+    let started = std::time::Instant::now();
     let res = match proc(req).await {
         Ok(res) => res,
-        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), .. Default::default() },
+        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), code: error::error_code(&*reason) },
     };
+    metrics::record_request("vote", started, res.status, res.code);
+    metrics::flush().await;
     Ok(Response::builder()
         .status(StatusCode::OK)
         .header("Content-Type", "application/json")
@@ -57,7 +62,8 @@ async fn proc(req: Request) -> Result<Res, Box<dyn std::error::Error>> {
 
     fn use_db(mut client: postgres::Client, req: Req) -> Result<Res, Box<dyn std::error::Error>> {
         // get token
-        let user_token = atcoder_api::parse_token(&req.token)?;
+        let user_token = auth::verify_token(&mut client, &req.token)?;
+        moderation::ensure_not_banned(&mut client, user_token.user_id)?;
 
         // get new vote
         let new_vote = match req.vote.as_str() {
@@ -66,79 +72,92 @@ async fn proc(req: Request) -> Result<Res, Box<dyn std::error::Error>> {
             "down" => -1i16,
             _ => return Err("invalid vote format (none|up|down)".into())
         };
-        
-        // get new rating
-        let mut new_rating = 0i16;
-        if new_vote != 0 {
-            let mut rating = None;
-            // 過去にレーティングを取得したのが 1 時間以内ならそれを使う
-            if let Ok(row) = client.query_one("SELECT rating, rating_last_update FROM users WHERE id = $1", &[&user_token.user_id]) {
-                let current_rating = row.get::<_, Option<i16>>(0);
-                let current_time = row.get::<_, Option<SystemTime>>(1);
-                if let Some((current_rating, current_time)) = current_rating.zip(current_time) {
-                    if SystemTime::now().duration_since(current_time)? <= Duration::from_secs(60 * 60) {
-                        rating = Some(current_rating);
-                    }
-                }
-            }
-            // 1 時間よりも古いなら新しく取得
-            if rating.is_none() {
-                let details = tokio::runtime::Builder::new_current_thread()
-                    .enable_all()
-                    .build()?
-                    .block_on(async move { atcoder_api::scrape_user(&user_token.atcoder_id).await })?;
-                let now_time = SystemTime::now();
-                rating = Some(details.rating);
-                // 保存
-                client.execute("UPDATE users SET rating = $1, rating_last_update = $2 WHERE id = $3", &[&details.rating, &now_time, &user_token.user_id])?;
-            }
-            new_rating = rating.unwrap();
+        let tags = req.tags.as_deref().map(vote_tags::normalize_tags).transpose()?;
+        if let Some(scores) = req.criteria.as_ref() {
+            criteria::validate_scores(scores)?;
         }
-
+        
         // get editorial_id
-        let editorial_id = {
+        let (editorial_id, contest, task, author) = {
             let Some(editorial_url) = atcoder_api::canonicalize_editorial_url(&req.editorial) else {
                 return Err("invalid editorial URL".into());
             };
 
-            if let Ok(row) = client.query_one("SELECT id FROM editorials WHERE editorial = $1", &[&editorial_url]) {
-                // already registered
-                row.get::<_, i32>(0)
-            } else {
+            if client.query_opt("SELECT 1 FROM editorials WHERE editorial = $1", &[&editorial_url])?.is_none() {
                 // register all editorials from same contest
-                let contest = req.contest.clone();
-                let editorials = tokio::runtime::Builder::new_current_thread()
-                    .enable_all()
-                    .build()?
-                    .block_on(async move { atcoder_api::scrape_editorials(&contest).await })?;
-                let statement = client.prepare("INSERT INTO editorials(editorial) VALUES($1) ON CONFLICT DO NOTHING")?;
-                for editorial in editorials {
-                    client.execute(&statement, &[&editorial])?;
-                }
+                editorials::register_contest_editorials(&mut client, &req.contest)?;
+            }
 
-                client.query_one("SELECT id FROM editorials WHERE editorial = $1", &[&editorial_url])?.get::<_, i32>(0)
+            let row = client.query_one(&format!("SELECT id, contest, task, author, {} FROM editorials WHERE editorial = $1", editorials::VISIBLE_SQL), &[&editorial_url])?;
+            if !row.get::<_, bool>(4) {
+                return Err("editorial not found".into());
             }
+            (row.get::<_, i32>(0), row.get::<_, Option<String>>(1), row.get::<_, Option<String>>(2), row.get::<_, Option<String>>(3))
         };
 
+        // コンテスト終了まで投票できない (取り消しはできる)
+        // the contest of the editorial is used, not `req.contest` (editorials registered before contests were recorded are not checked)
+        if new_vote != 0 {
+            if let Some(contest) = contest.as_deref() {
+                contests::ensure_ended(&mut client, contest)?;
+            }
+        }
+
+        // 自分の解説には投票できない
+        if new_vote != 0 && author.is_some_and(|author| author.eq_ignore_ascii_case(&user_token.atcoder_id) ) {
+            match config::self_vote_policy()? {
+                SelfVotePolicy::Reject => return Err(ApiError::new("self_vote", "cannot vote on your own editorial").into()),
+                SelfVotePolicy::Ignore => return Ok(Res { status: "success", .. Default::default() }),
+            }
+        }
+
+        // get new rating (heuristic rating for heuristic contests)
+        let mut new_rating = None;
+        let mut attempt = None;
+        if new_vote != 0 {
+            let heuristic = match contest.as_deref() {
+                Some(contest) => contests::get_contest(&mut client, contest)?.heuristic,
+                None => false,
+            };
+            new_rating = user_rating::get_rating(&mut client, user_token.user_id, &user_token.atcoder_id, heuristic)?;
+            if new_rating.is_none() && config::unrated_policy()? == UnratedPolicy::Deny {
+                return Err(ApiError::new(eligibility::INELIGIBLE, "unrated users cannot vote").into());
+            }
+            // check eligibility rules (tokens may have been created before the rules changed)
+            eligibility::check_eligibility(&mut client, user_token.user_id, &user_token.atcoder_id)?;
+            // check submissions to the task (not checked for editorials registered before contests were recorded)
+            if let Some(contest) = contest.as_deref() {
+                attempt = eligibility::check_attempt(&mut client, user_token.user_id, contest, task.as_deref(), &user_token.atcoder_id)?.map(|attempt| attempt.as_i16() );
+            }
+        }
+
         // vote
         {
             let mut tx = client.transaction()?;
             // if old vote exist, revert vote_temp
-            tx.execute("UPDATE vote_temp SET score = vote_temp.score - CAST(votes.score AS INTEGER) FROM votes WHERE votes.editorial_id = $1 AND votes.user_id = $2 AND vote_temp.editorial_id = $1 AND vote_temp.rating_level = votes.rating / 100", &[&editorial_id, &user_token.user_id])?;
+            aggregate::subtract_votes(&mut tx, user_token.user_id, Some(editorial_id))?;
 
             // apply new vote
             if new_vote == 0 {
                 // delete vote
                 tx.execute("DELETE FROM votes WHERE user_id = $1 AND editorial_id = $2", &[&user_token.user_id, &editorial_id])?;
             } else {
-                // replace old vote by new vote
-                tx.execute("INSERT INTO votes(user_id, editorial_id, score, rating) VALUES($1, $2, $3, $4) ON CONFLICT (user_id, editorial_id) DO UPDATE SET score = $3, rating = $4", &[&user_token.user_id, &editorial_id, &new_vote, &new_rating])?;
+                // replace old vote by new vote (keep the old attempt if not checked, and the old tags if not given)
+                tx.execute("INSERT INTO votes(user_id, editorial_id, score, rating, rating_level, attempt, voted_at, tags) VALUES($1, $2, $3, $4, $5, $6, $7, COALESCE($8, '{}')) ON CONFLICT (user_id, editorial_id) DO UPDATE SET score = $3, rating = $4, rating_level = $5, attempt = COALESCE($6, votes.attempt), voted_at = $7, tags = COALESCE($8, votes.tags)", &[&user_token.user_id, &editorial_id, &new_vote, &new_rating, &rating_bucket::rating_level(new_rating), &attempt, &std::time::SystemTime::now(), &tags])?;
+                // replace old criterion scores if given
+                if let Some(scores) = req.criteria.as_ref() {
+                    tx.execute("DELETE FROM criterion_scores WHERE user_id = $1 AND editorial_id = $2", &[&user_token.user_id, &editorial_id])?;
+                    for (criterion, score) in scores {
+                        tx.execute("INSERT INTO criterion_scores(user_id, editorial_id, criterion, score) VALUES($1, $2, $3, $4)", &[&user_token.user_id, &editorial_id, criterion, score])?;
+                    }
+                }
                 // update vote_temp
-                tx.execute("INSERT INTO vote_temp(editorial_id, rating_level, score) VALUES($1, $2, $3) ON CONFLICT (editorial_id, rating_level) DO UPDATE SET score = vote_temp.score + $3", &[&editorial_id, &(new_rating / 100), &(new_vote as i32)])?;
+                aggregate::add_votes(&mut tx, user_token.user_id, Some(editorial_id))?;
             }
 
             tx.commit()?;
         }
+        metrics::inc_counter(metrics::VOTES, &[("vote", &req.vote)]);
     
         Ok(Res {
             status: "success",
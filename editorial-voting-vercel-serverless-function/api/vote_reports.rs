@@ -0,0 +1,106 @@
+use editorial_voting_vercel_serverless_function::{auth, database, error, metrics, moderation::{self, Role}, vote_analysis};
+use vercel_runtime::{process_request, process_response, run_service, Body, Error, Request, RequestPayloadExt, Response, ServiceBuilder, StatusCode};
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+struct Req {
+    token: String,
+    include_reviewed: Option<bool>,
+    limit: Option<i64>,
+    review: Option<Vec<i32>>,
+}
+
+#[derive(serde::Serialize, Default, Debug)]
+struct Res {
+    status: &'static str,
+    reason: Option<String>,
+    code: Option<&'static str>,
+    reports: Option<Vec<Report>>,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct Report {
+    id: i32,
+    kind: String,
+    users: Vec<String>,
+    details: serde_json::Value,
+    created_at: u64,
+    reviewed_by: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let handler = ServiceBuilder::new()
+        .map_request(process_request)
+        .map_response(process_response)
+        .service_fn(handler);
+
+    run_service(handler).await
+}
+
+pub async fn handler(req: Request) -> Result<Response<Body>, Error> {
+    if req.method() == "OPTIONS" {
+        return Ok(Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .header("Access-Control-Allow-Origin", "*")
+            .header("Access-Control-Allow-Methods", "*")
+            .header("Access-Control-Allow-Headers", "*")
+            .header("Access-Control-Max-Age", "86400")
+            .body(Body::Empty)?);
+    }
+    let started = std::time::Instant::now();
+    let res = match proc(req).await {
+        Ok(res) => res,
+        Err(reason) => Res { status: "error", reason: Some(reason.to_string()), code: error::error_code(&*reason), .. Default::default() },
+    };
+    metrics::record_request("vote_reports", started, res.status, res.code);
+    metrics::flush().await;
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "application/json")
+        .header("Access-Control-Allow-Origin", "*")
+        .header("Access-Control-Allow-Headers", "*")
+        .body(Body::Text(serde_json::to_string(&res)?))?)
+}
+
+async fn proc(req: Request) -> Result<Res, Box<dyn std::error::Error>> {
+    let Ok(Some(req)) = req.payload::<Req>() else {
+        return Err("invalid request".into());
+    };
+    let limit = req.limit.unwrap_or(50);
+    if !(1 ..= 200).contains(&limit) {
+        return Err("limit must be between 1 and 200".into());
+    }
+
+    fn use_db(mut client: postgres::Client, (req, limit): (Req, i64)) -> Result<Res, Box<dyn std::error::Error>> {
+        let user_token = auth::verify_token(&mut client, &req.token)?;
+        moderation::require_role(&mut client, user_token.user_id, Role::Moderator)?;
+
+        // mark as reviewed
+        if let Some(review) = req.review.as_ref() {
+            vote_analysis::review_reports(&mut client, review, &user_token.atcoder_id)?;
+        }
+
+        let reports = vote_analysis::list_reports(&mut client, req.include_reviewed.unwrap_or(false), limit)?;
+        // show AtCoder IDs instead of user ids
+        let user_ids = reports.iter().flat_map(|report| report.user_ids.iter().copied() ).collect::<Vec<_>>();
+        let atcoder_ids = client.query("SELECT id, atcoder_id FROM users WHERE id = ANY($1)", &[&user_ids])?.into_iter().map(|row| (row.get::<_, i32>(0), row.get::<_, String>(1)) ).collect::<std::collections::HashMap<_, _>>();
+        let reports = reports.into_iter().map(|report| -> Result<Report, Box<dyn std::error::Error>> {
+            Ok(Report {
+                id: report.id,
+                kind: report.kind,
+                users: report.user_ids.iter().filter_map(|user_id| atcoder_ids.get(user_id).cloned() ).collect(),
+                details: report.details,
+                created_at: report.created_at.duration_since(std::time::UNIX_EPOCH)?.as_secs(),
+                reviewed_by: report.reviewed_by,
+            })
+        }).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Res {
+            status: "success",
+            reports: Some(reports),
+            .. Default::default()
+        })
+    }
+
+    Ok(database::with_database(use_db, (req, limit)).await?)
+}
\ No newline at end of file
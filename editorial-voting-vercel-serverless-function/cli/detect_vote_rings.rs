@@ -0,0 +1,38 @@
+//! Detect suspicious voting patterns (vote rings and brigading) and save them to `vote_reports` for moderators to review.
+//!
+//! Options:
+//! - `--recent-days <days>`: only votes within this many days are examined (default: 90)
+//! - `--window-secs <secs>`: votes in the same direction on the same author within this window are grouped (default: 3600)
+//! - `--fresh-days <days>`: users who voted within this many days since their first token are fresh accounts (default: 7)
+//! - `--min-group <n>`: minimum number of fresh accounts in a group to report (default: 5)
+//! - `--min-common <n>`: minimum number of editorials voted by both users of a pair to compare (default: 10)
+//! - `--min-agreement <ratio>`: minimum ratio of the same votes among common editorials to report a pair (default: 0.95)
+
+use std::time::Duration;
+
+use editorial_voting_vercel_serverless_function::{database, vote_analysis::{self, DetectionOptions, DetectionSummary}};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut options = DetectionOptions::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("missing value of {arg}") )?;
+        match arg.as_str() {
+            "--recent-days" => options.recent = Duration::from_secs(value.parse::<u64>()? * 24 * 60 * 60),
+            "--window-secs" => options.window = Duration::from_secs(value.parse()?),
+            "--fresh-days" => options.fresh_age = Duration::from_secs(value.parse::<u64>()? * 24 * 60 * 60),
+            "--min-group" => options.min_group = value.parse()?,
+            "--min-common" => options.min_common = value.parse()?,
+            "--min-agreement" => options.min_agreement = value.parse()?,
+            _ => return Err(format!("unknown option {arg}").into()),
+        }
+    }
+
+    fn use_db(mut client: postgres::Client, options: DetectionOptions) -> Result<DetectionSummary, Box<dyn std::error::Error>> {
+        vote_analysis::detect_vote_rings(&mut client, &options)
+    }
+    let summary = database::with_database(use_db, options).await?;
+    println!("new reports: {} bursts, {} similar pairs", summary.bursts, summary.similar_pairs);
+    Ok(())
+}
\ No newline at end of file
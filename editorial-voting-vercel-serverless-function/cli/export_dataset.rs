@@ -0,0 +1,46 @@
+//! Export the editorials and their votes by rating level as a dataset for offline analysis.
+//!
+//! Options:
+//! - `--out <dir>`: output directory (required)
+//! - `--format <jsonl|csv>`: format of the tables (default: jsonl)
+//! - `--votes`: also export each vote, with the voter replaced by a pseudonym derived from `EDITORIAL_VOTING_DATASET_SALT` (without the exact rating, and the time rounded down to the day)
+//! - `--backup`: export all users, editorials and votes with AtCoder IDs, to be restored by `import_dataset`
+
+use std::path::PathBuf;
+
+use editorial_voting_vercel_serverless_function::{database, dataset::{self, Dataset, ExportMode, Format}};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut out = None;
+    let mut format = Format::Jsonl;
+    let mut include_votes = false;
+    let mut backup = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--votes" => include_votes = true,
+            "--backup" => backup = true,
+            "--out" => out = Some(PathBuf::from(args.next().ok_or("missing value of --out")?)),
+            "--format" => format = Format::parse(&args.next().ok_or("missing value of --format")?).ok_or("invalid format (jsonl|csv)")?,
+            _ => return Err(format!("unknown option {arg}").into()),
+        }
+    }
+    let out = out.ok_or("--out is required")?;
+    let mode = if backup {
+        ExportMode::Backup
+    } else if include_votes {
+        ExportMode::PublicWithVotes { salt: std::env::var("EDITORIAL_VOTING_DATASET_SALT").map_err(|_| "EDITORIAL_VOTING_DATASET_SALT is required to export votes" )? }
+    } else {
+        ExportMode::Public
+    };
+
+    fn use_db(mut client: postgres::Client, mode: ExportMode) -> Result<Dataset, Box<dyn std::error::Error>> {
+        dataset::read_dataset(&mut client, &mode)
+    }
+    let dataset = database::with_database(use_db, mode).await?;
+    let meta = dataset::write_dataset(&out, format, &dataset)?;
+
+    println!("exported {} (schema version {}) to {}", meta.tables.join(", "), meta.schema_version, out.display());
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,34 @@
+//! Import a backup written by `export_dataset --backup` (users, editorials and votes) and rebuild `vote_temp`.
+//!
+//! Existing rows with the same AtCoder ID, editorial URL or (user, editorial) pair are replaced, so importing the same backup twice is harmless.
+//! Backups are partial, so they cannot be imported into a database with users not in the backup.
+//! Nothing is imported if a vote refers to a user or an editorial that is neither in the backup nor in the database.
+//!
+//! Options:
+//! - `--dir <dir>`: directory of the dataset (required)
+
+use std::path::PathBuf;
+
+use editorial_voting_vercel_serverless_function::{database, dataset::{self, Dataset, ImportSummary, Meta}};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut dir = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dir" => dir = Some(PathBuf::from(args.next().ok_or("missing value of --dir")?)),
+            _ => return Err(format!("unknown option {arg}").into()),
+        }
+    }
+    let dir = dir.ok_or("--dir is required")?;
+    let (meta, dataset) = dataset::load_dataset(&dir)?;
+
+    fn use_db(mut client: postgres::Client, (meta, dataset): (Meta, Dataset)) -> Result<ImportSummary, Box<dyn std::error::Error>> {
+        dataset::import_dataset(&mut client, &meta, &dataset)
+    }
+    let summary = database::with_database(use_db, (meta, dataset)).await?;
+
+    println!("imported {} users, {} editorials and {} votes from {}", summary.users, summary.editorials, summary.votes, dir.display());
+    Ok(())
+}
\ No newline at end of file
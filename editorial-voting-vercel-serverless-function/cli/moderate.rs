@@ -0,0 +1,61 @@
+//! Ban users, hide editorials and give roles to users. Every operation is recorded in `moderation_log`.
+//!
+//! Usage:
+//! - `ban <atcoder_id> <reason>` / `unban <atcoder_id> <reason>`: votes of banned users are removed from the aggregates
+//! - `hide <editorial> <reason>` / `unhide <editorial> <reason>`: hidden editorials are not returned by any endpoint
+//! - `approve <editorial> <reason>` / `reject <editorial> <reason>`: review editorials submitted by users
+//! - `merge <from> <into> <reason>`: move votes on the editorial `from` to `into` and hide `from`
+//! - `rescrape <contest> <reason>`: register editorials of the contest again
+//! - `role <atcoder_id> (voter|moderator|admin) <reason>`: moderators and admins can use the admin endpoints
+//! - `log [<limit>]`: show the latest operations (default: 20)
+
+use editorial_voting_vercel_serverless_function::{database, moderation::{self, Role, CLI_MODERATOR}};
+
+enum Command {
+    Ban(String, bool, String),
+    Hide(String, bool, String),
+    Review(String, bool, String),
+    Merge(String, String, String),
+    Rescrape(String, String),
+    Role(String, Role, String),
+    Log(i64),
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let command = match args.iter().map(|arg| arg.as_str() ).collect::<Vec<_>>().as_slice() {
+        ["ban", atcoder_id, reason] => Command::Ban(atcoder_id.to_string(), true, reason.to_string()),
+        ["unban", atcoder_id, reason] => Command::Ban(atcoder_id.to_string(), false, reason.to_string()),
+        ["hide", editorial, reason] => Command::Hide(editorial.to_string(), true, reason.to_string()),
+        ["unhide", editorial, reason] => Command::Hide(editorial.to_string(), false, reason.to_string()),
+        ["approve", editorial, reason] => Command::Review(editorial.to_string(), true, reason.to_string()),
+        ["reject", editorial, reason] => Command::Review(editorial.to_string(), false, reason.to_string()),
+        ["merge", from, into, reason] => Command::Merge(from.to_string(), into.to_string(), reason.to_string()),
+        ["rescrape", contest, reason] => Command::Rescrape(contest.to_string(), reason.to_string()),
+        ["role", atcoder_id, role, reason] => Command::Role(atcoder_id.to_string(), Role::parse(role).ok_or("invalid role (voter|moderator|admin)")?, reason.to_string()),
+        ["log"] => Command::Log(20),
+        ["log", limit] => Command::Log(limit.parse()?),
+        _ => return Err("usage: moderate (ban|unban <atcoder_id> | hide|unhide|approve|reject <editorial> | merge <from> <into> | rescrape <contest> | role <atcoder_id> <role>) <reason> | log [<limit>]".into()),
+    };
+
+    fn use_db(mut client: postgres::Client, command: Command) -> Result<(), Box<dyn std::error::Error>> {
+        match command {
+            Command::Ban(atcoder_id, banned, reason) => moderation::set_banned(&mut client, &atcoder_id, banned, CLI_MODERATOR, None, &reason)?,
+            Command::Hide(editorial, hidden, reason) => moderation::set_hidden(&mut client, &editorial, hidden, CLI_MODERATOR, &reason)?,
+            Command::Review(editorial, approved, reason) => moderation::review_editorial(&mut client, &editorial, approved, CLI_MODERATOR, &reason)?,
+            Command::Merge(from, into, reason) => moderation::merge_editorials(&mut client, &from, &into, CLI_MODERATOR, &reason)?,
+            Command::Rescrape(contest, reason) => moderation::rescrape_contest(&mut client, &contest, CLI_MODERATOR, &reason)?,
+            Command::Role(atcoder_id, role, reason) => moderation::set_role(&mut client, &atcoder_id, role, CLI_MODERATOR, &reason)?,
+            Command::Log(limit) => {
+                for entry in moderation::recent_log(&mut client, limit)? {
+                    let time = entry.created_at.duration_since(std::time::UNIX_EPOCH)?.as_secs();
+                    println!("{time}\t{}\t{}\t{}\t{}", entry.moderator, entry.action, entry.target, entry.reason);
+                }
+            }
+        }
+        Ok(())
+    }
+    database::with_database(use_db, command).await?;
+    Ok(())
+}
\ No newline at end of file
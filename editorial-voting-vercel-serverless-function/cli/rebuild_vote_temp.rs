@@ -0,0 +1,18 @@
+//! Recompute the rating bucket of every vote by `EDITORIAL_VOTING_RATING_POLICY` and rebuild `vote_temp` from `votes`.
+//! Run this after changing the rating policy.
+
+use editorial_voting_vercel_serverless_function::{aggregate, config, database};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    fn use_db(mut client: postgres::Client, _: ()) -> Result<(), Box<dyn std::error::Error>> {
+        let mut tx = client.transaction()?;
+        aggregate::rebuild_vote_temp(&mut tx)?;
+        tx.commit()?;
+        Ok(())
+    }
+    database::with_database(use_db, ()).await?;
+
+    println!("rebuilt vote_temp (rating policy: {})", config::rating_policy()?.as_str());
+    Ok(())
+}
@@ -0,0 +1,33 @@
+//! Refresh the ratings of all users who have voted, so that `/vote` does not need to scrape AtCoder.
+//! Run this after rated contests.
+//!
+//! Options:
+//! - `--concurrency <n>`: maximum number of users scraped concurrently (default: 2)
+//! - `--interval-ms <ms>`: minimum interval between starting to scrape users, each of which takes 4 requests to AtCoder (default: 4000)
+//! - `--max-age-secs <secs>`: skip users whose rating was fetched more recently than this (default: 3600)
+
+use std::time::Duration;
+
+use editorial_voting_vercel_serverless_function::rating_refresh::{self, RefreshOptions};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut options = RefreshOptions::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| format!("missing value of {arg}") )?;
+        match arg.as_str() {
+            "--concurrency" => options.concurrency = value.parse()?,
+            "--interval-ms" => options.interval = Duration::from_millis(value.parse()?),
+            "--max-age-secs" => options.max_age = Duration::from_secs(value.parse()?),
+            _ => return Err(format!("unknown option {arg}").into()),
+        }
+    }
+
+    let summary = rating_refresh::refresh_voter_ratings(&options).await?;
+    for (atcoder_id, reason) in &summary.failures {
+        eprintln!("failed to fetch rating of {atcoder_id}: {reason}");
+    }
+    println!("updated: {}, failed: {}", summary.updated, summary.failures.len());
+    Ok(())
+}
@@ -0,0 +1,12 @@
+--PostgreSQL
+
+ALTER TABLE vote_temp ADD COLUMN up INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE vote_temp ADD COLUMN down INTEGER NOT NULL DEFAULT 0;
+
+UPDATE vote_temp SET up = counts.up, down = counts.down
+FROM (
+    SELECT editorial_id, rating / 100 AS rating_level, COUNT(*) FILTER (WHERE score = 1) AS up, COUNT(*) FILTER (WHERE score = -1) AS down
+    FROM votes
+    GROUP BY editorial_id, rating / 100
+) AS counts
+WHERE vote_temp.editorial_id = counts.editorial_id AND vote_temp.rating_level = counts.rating_level;
@@ -0,0 +1,6 @@
+--PostgreSQL
+
+ALTER TABLE editorials ADD COLUMN contest TEXT;
+ALTER TABLE editorials ADD COLUMN task TEXT;
+
+CREATE INDEX editorials_contest ON editorials (contest);
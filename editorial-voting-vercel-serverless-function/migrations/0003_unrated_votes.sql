@@ -0,0 +1,4 @@
+--PostgreSQL
+
+-- NULL rating means the voter was unrated (counted in vote_temp.rating_level = -1)
+ALTER TABLE votes ALTER COLUMN rating DROP NOT NULL;
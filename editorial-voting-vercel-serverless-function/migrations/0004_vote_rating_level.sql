@@ -0,0 +1,5 @@
+--PostgreSQL
+
+ALTER TABLE votes ADD COLUMN rating_level SMALLINT;
+UPDATE votes SET rating_level = COALESCE(rating / 100, -1);
+ALTER TABLE votes ALTER COLUMN rating_level SET NOT NULL;
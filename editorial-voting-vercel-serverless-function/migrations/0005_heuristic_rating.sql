@@ -0,0 +1,6 @@
+--PostgreSQL
+
+ALTER TABLE users ADD COLUMN heuristic_rating SMALLINT;
+
+-- heuristic ratings are fetched on the next refresh
+UPDATE users SET rating_last_update = NULL;
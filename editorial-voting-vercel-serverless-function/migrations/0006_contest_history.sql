@@ -0,0 +1,7 @@
+--PostgreSQL
+
+ALTER TABLE users ADD COLUMN rated_contests SMALLINT;
+ALTER TABLE users ADD COLUMN first_contest_time TIMESTAMP;
+
+-- contest histories are fetched on the next refresh
+UPDATE users SET rating_last_update = NULL;
@@ -0,0 +1,3 @@
+--PostgreSQL
+
+ALTER TABLE editorials ADD COLUMN author VARCHAR(16);
@@ -0,0 +1,10 @@
+--PostgreSQL
+
+CREATE TABLE contests (
+    -- contest ID (e.g. abc123)
+    contest TEXT PRIMARY KEY,
+    start_time TIMESTAMP NOT NULL,
+    end_time TIMESTAMP NOT NULL,
+    -- time the contest page was scraped
+    last_update TIMESTAMP NOT NULL
+);
@@ -0,0 +1,16 @@
+--PostgreSQL
+
+ALTER TABLE users ADD COLUMN banned BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE editorials ADD COLUMN hidden BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE moderation_log (
+    id SERIAL PRIMARY KEY,
+    -- AtCoder ID of the moderator, or `cli`
+    moderator TEXT NOT NULL,
+    -- ban, unban, hide, unhide
+    action TEXT NOT NULL,
+    -- AtCoder ID of the user or URL of the editorial
+    target TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,3 @@
+--PostgreSQL
+
+ALTER TABLE users ADD COLUMN role TEXT NOT NULL DEFAULT 'voter';
@@ -0,0 +1,18 @@
+--PostgreSQL
+
+ALTER TABLE users ADD COLUMN created_at TIMESTAMP;
+ALTER TABLE votes ADD COLUMN voted_at TIMESTAMP;
+
+CREATE TABLE vote_reports (
+    id SERIAL PRIMARY KEY,
+    -- identifies the finding, so that running the detection again does not duplicate it
+    fingerprint TEXT UNIQUE NOT NULL,
+    -- burst, similar_pair
+    kind TEXT NOT NULL,
+    user_ids INTEGER[] NOT NULL,
+    details JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    -- AtCoder ID of the moderator, NULL if not reviewed yet
+    reviewed_by TEXT,
+    reviewed_at TIMESTAMP
+);
@@ -0,0 +1,11 @@
+--PostgreSQL
+
+ALTER TABLE editorials ADD COLUMN status TEXT NOT NULL DEFAULT 'approved';
+ALTER TABLE editorials ADD COLUMN submitted_by INTEGER REFERENCES users (id);
+
+CREATE TABLE editorial_endorsements (
+    editorial_id INTEGER NOT NULL REFERENCES editorials (id),
+    user_id INTEGER NOT NULL REFERENCES users (id),
+    created_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (editorial_id, user_id)
+);
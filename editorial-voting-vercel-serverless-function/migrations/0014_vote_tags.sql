@@ -0,0 +1,3 @@
+--PostgreSQL
+
+ALTER TABLE votes ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,12 @@
+--PostgreSQL
+
+CREATE TABLE error_reports (
+    id SERIAL PRIMARY KEY,
+    editorial_id INTEGER NOT NULL REFERENCES editorials (id),
+    user_id INTEGER NOT NULL REFERENCES users (id),
+    description TEXT NOT NULL,
+    counterexample TEXT,
+    created_at TIMESTAMP NOT NULL,
+    -- one report per user on each editorial
+    UNIQUE (editorial_id, user_id)
+);
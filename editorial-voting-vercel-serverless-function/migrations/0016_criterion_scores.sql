@@ -0,0 +1,13 @@
+--PostgreSQL
+
+CREATE TABLE criterion_scores (
+    user_id INTEGER NOT NULL,
+    editorial_id INTEGER NOT NULL,
+    -- criteria::CRITERIA (clarity, correctness, depth)
+    criterion TEXT NOT NULL,
+    -- 1 .. 5
+    score SMALLINT NOT NULL,
+    PRIMARY KEY (user_id, editorial_id, criterion),
+    -- deleted with the vote
+    FOREIGN KEY (user_id, editorial_id) REFERENCES votes (user_id, editorial_id) ON UPDATE CASCADE ON DELETE CASCADE
+);
@@ -0,0 +1,14 @@
+--PostgreSQL
+
+CREATE TABLE metric_samples (
+    -- metrics name (e.g. editorial_voting_request_duration_seconds)
+    metric TEXT NOT NULL,
+    -- '' for counters, '_bucket', '_sum' or '_count' for histograms
+    suffix TEXT NOT NULL,
+    -- labels in the Prometheus text format without braces (e.g. endpoint="vote")
+    labels TEXT NOT NULL,
+    -- upper bound of the histogram bucket, 0 for other samples
+    le DOUBLE PRECISION NOT NULL,
+    value DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (metric, suffix, labels, le)
+);
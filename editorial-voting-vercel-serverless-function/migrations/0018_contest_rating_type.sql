@@ -0,0 +1,4 @@
+--PostgreSQL
+
+-- NULL if scraped before the rating type was recorded (scraped again by contests::get_contest)
+ALTER TABLE contests ADD COLUMN heuristic BOOLEAN;
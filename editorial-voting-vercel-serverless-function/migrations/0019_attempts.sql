@@ -0,0 +1,13 @@
+--PostgreSQL
+
+CREATE TABLE attempts (
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    contest TEXT NOT NULL,
+    -- task ID, '' for any task of the contest
+    task TEXT NOT NULL,
+    -- atcoder_api::Attempt (0: not submitted, 1: submitted, 2: accepted)
+    attempt SMALLINT NOT NULL,
+    -- time the submissions were scraped
+    checked_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (user_id, contest, task)
+);
@@ -0,0 +1,4 @@
+--PostgreSQL
+
+-- used by detect_vote_rings to read only recent votes
+CREATE INDEX votes_voted_at ON votes (voted_at);
@@ -5,27 +5,161 @@ CREATE TABLE users (
     id SERIAL PRIMARY KEY,
     -- /^[0-9A-Za-z]{3,16}$/
     atcoder_id VARCHAR(16) UNIQUE NOT NULL,
-    -- 0 .. 9999
+    -- algorithm rating, 0 .. 9999, NULL if unrated
     rating SMALLINT,
-    rating_last_update TIMESTAMP
+    -- heuristic rating (used for editorials of AHC), 0 .. 9999, NULL if unrated
+    heuristic_rating SMALLINT,
+    -- number of rated contests (algorithm and heuristic)
+    rated_contests SMALLINT,
+    -- end time of the first contest the user participated in
+    first_contest_time TIMESTAMP,
+    -- NULL if rating (and the contest history) has never been fetched
+    rating_last_update TIMESTAMP,
+    -- votes of banned users are excluded from vote_temp
+    banned BOOLEAN NOT NULL DEFAULT FALSE,
+    -- voter, moderator or admin
+    role TEXT NOT NULL DEFAULT 'voter',
+    -- time the first token was created, NULL if created before it was recorded
+    created_at TIMESTAMP
 );
 
 CREATE TABLE editorials (
     id SERIAL PRIMARY KEY,
-    editorial TEXT UNIQUE NOT NULL
+    editorial TEXT UNIQUE NOT NULL,
+    -- contest ID (e.g. abc123), NULL if registered before it was recorded
+    contest TEXT,
+    -- task ID (e.g. abc123_a), NULL for the overall editorial
+    task TEXT,
+    -- AtCoder ID of the writer, NULL if unknown
+    author VARCHAR(16),
+    -- hidden editorials are not returned by any endpoint
+    hidden BOOLEAN NOT NULL DEFAULT FALSE,
+    -- pending, approved or rejected (only editorials submitted by users can be pending or rejected)
+    status TEXT NOT NULL DEFAULT 'approved',
+    -- user who submitted the editorial, NULL for editorials on the AtCoder editorial page
+    submitted_by INTEGER REFERENCES users (id)
 );
 
+CREATE INDEX editorials_contest ON editorials (contest);
+
 CREATE TABLE votes (
     user_id INTEGER NOT NULL REFERENCES users (id),
     editorial_id INTEGER NOT NULL REFERENCES editorials (id),
     score SMALLINT NOT NULL,
-    rating SMALLINT NOT NULL,
+    -- rating of the user at the time of voting (heuristic rating for AHC), NULL if unrated
+    rating SMALLINT,
+    -- rating level the vote is counted in vote_temp (by EDITORIAL_VOTING_RATING_POLICY), -1 for unrated
+    rating_level SMALLINT NOT NULL,
+    -- submissions of the user to the task (EDITORIAL_VOTING_ATTEMPT_POLICY): 0 none, 1 submitted, 2 accepted, NULL if not checked
+    attempt SMALLINT,
+    -- time of the last change of the vote, NULL if voted before it was recorded
+    voted_at TIMESTAMP,
+    -- tags attached by the voter (vote_tags::TAGS)
+    tags TEXT[] NOT NULL DEFAULT '{}',
     PRIMARY KEY (user_id, editorial_id)
 );
 
+CREATE INDEX votes_voted_at ON votes (voted_at);
+
 CREATE TABLE vote_temp (
     editorial_id INTEGER NOT NULL REFERENCES editorials (id),
+    -- rating / 100, -1 for unrated
     rating_level SMALLINT NOT NULL,
     score INTEGER NOT NULL,
+    -- number of up-votes / down-votes
+    up INTEGER NOT NULL DEFAULT 0,
+    down INTEGER NOT NULL DEFAULT 0,
     PRIMARY KEY (editorial_id, rating_level)
+);
+
+CREATE TABLE contests (
+    -- contest ID (e.g. abc123)
+    contest TEXT PRIMARY KEY,
+    start_time TIMESTAMP NOT NULL,
+    end_time TIMESTAMP NOT NULL,
+    -- rated by the heuristic rating (AHC etc.), NULL if scraped before it was recorded
+    heuristic BOOLEAN,
+    -- time the contest page was scraped
+    last_update TIMESTAMP NOT NULL
+);
+
+CREATE TABLE moderation_log (
+    id SERIAL PRIMARY KEY,
+    -- AtCoder ID of the moderator, or `cli`
+    moderator TEXT NOT NULL,
+    -- ban, unban, hide, unhide, merge, rescrape, role, approve, reject
+    action TEXT NOT NULL,
+    -- AtCoder ID of the user, URL of the editorial or contest ID
+    target TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
+
+CREATE TABLE vote_reports (
+    id SERIAL PRIMARY KEY,
+    -- identifies the finding, so that running the detection again does not duplicate it
+    fingerprint TEXT UNIQUE NOT NULL,
+    -- burst, similar_pair
+    kind TEXT NOT NULL,
+    user_ids INTEGER[] NOT NULL,
+    details JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL,
+    -- AtCoder ID of the moderator, NULL if not reviewed yet
+    reviewed_by TEXT,
+    reviewed_at TIMESTAMP
+);
+
+CREATE TABLE editorial_endorsements (
+    editorial_id INTEGER NOT NULL REFERENCES editorials (id),
+    user_id INTEGER NOT NULL REFERENCES users (id),
+    created_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (editorial_id, user_id)
+);
+
+CREATE TABLE error_reports (
+    id SERIAL PRIMARY KEY,
+    editorial_id INTEGER NOT NULL REFERENCES editorials (id),
+    user_id INTEGER NOT NULL REFERENCES users (id),
+    description TEXT NOT NULL,
+    counterexample TEXT,
+    created_at TIMESTAMP NOT NULL,
+    -- one report per user on each editorial
+    UNIQUE (editorial_id, user_id)
+);
+
+CREATE TABLE criterion_scores (
+    user_id INTEGER NOT NULL,
+    editorial_id INTEGER NOT NULL,
+    -- criteria::CRITERIA (clarity, correctness, depth)
+    criterion TEXT NOT NULL,
+    -- 1 .. 5
+    score SMALLINT NOT NULL,
+    PRIMARY KEY (user_id, editorial_id, criterion),
+    -- deleted with the vote
+    FOREIGN KEY (user_id, editorial_id) REFERENCES votes (user_id, editorial_id) ON UPDATE CASCADE ON DELETE CASCADE
+);
+
+CREATE TABLE metric_samples (
+    -- metrics name (e.g. editorial_voting_request_duration_seconds)
+    metric TEXT NOT NULL,
+    -- '' for counters, '_bucket', '_sum' or '_count' for histograms
+    suffix TEXT NOT NULL,
+    -- labels in the Prometheus text format without braces (e.g. endpoint="vote")
+    labels TEXT NOT NULL,
+    -- upper bound of the histogram bucket, 0 for other samples
+    le DOUBLE PRECISION NOT NULL,
+    value DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (metric, suffix, labels, le)
+);
+
+CREATE TABLE attempts (
+    user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    contest TEXT NOT NULL,
+    -- task ID, '' for any task of the contest
+    task TEXT NOT NULL,
+    -- atcoder_api::Attempt (0: not submitted, 1: submitted, 2: accepted)
+    attempt SMALLINT NOT NULL,
+    -- time the submissions were scraped
+    checked_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (user_id, contest, task)
 );
\ No newline at end of file
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use crate::{atcoder_api::Attempt, config::{self, RatingPolicy}, criteria::{self, CriterionStats, CriterionSummary}, ranking::{self, CompositeOptions, VoteCounts}, rating_bucket::{self, BucketScheme}, user_rating::LATEST_RATING_SQL};
+
+#[derive(Clone, Default, Debug)]
+pub struct Aggregate {
+    pub counts: VoteCounts,
+    pub counts_by_bucket: HashMap<String, VoteCounts>,
+    /// votes of users who got AC on the task
+    pub solver_counts: VoteCounts,
+    /// number of votes with each tag
+    pub tag_counts: HashMap<String, i64>,
+    /// number of users who reported a mistake in the editorial
+    pub error_reports: i64,
+    /// scores of each criterion
+    pub criteria: HashMap<String, CriterionStats>,
+    pub criteria_by_bucket: HashMap<String, HashMap<String, CriterionStats>>,
+}
+
+impl Aggregate {
+    fn add(&mut self, bucket: Option<String>, counts: VoteCounts) {
+        self.counts.add(counts);
+        if let Some(bucket) = bucket {
+            self.counts_by_bucket.entry(bucket).or_default().add(counts);
+        }
+    }
+
+    pub fn scores_by_rating(&self) -> HashMap<String, i64> {
+        self.counts_by_bucket.iter().map(|(bucket, counts)| (bucket.clone(), counts.score()) ).collect()
+    }
+
+    pub fn composite_score(&self, options: &CompositeOptions) -> Option<f64> {
+        ranking::composite_score(&self.counts_by_bucket, options)
+    }
+
+    fn add_criterion(&mut self, bucket: Option<String>, criterion: String, stats: CriterionStats) {
+        if let Some(bucket) = bucket {
+            self.criteria_by_bucket.entry(bucket).or_default().entry(criterion.clone()).or_default().add(stats);
+        }
+        self.criteria.entry(criterion).or_default().add(stats);
+    }
+
+    pub fn criteria_by_rating(&self) -> HashMap<String, HashMap<String, CriterionSummary>> {
+        self.criteria_by_bucket.iter().map(|(bucket, stats)| (bucket.clone(), criteria::summaries(stats)) ).collect()
+    }
+}
+
+/// aggregate votes of editorials into the buckets of the scheme
+pub fn aggregate_editorials(client: &mut postgres::Client, editorial_ids: &[i32], scheme: &BucketScheme) -> Result<HashMap<i32, Aggregate>, Box<dyn std::error::Error>> {
+    let mut aggregates = HashMap::<i32, Aggregate>::new();
+    let rating_policy = config::rating_policy()?;
+    if scheme.is_level_aligned() {
+        let rows = client.query("SELECT editorial_id, rating_level, up, down FROM vote_temp WHERE editorial_id = ANY($1)", &[&editorial_ids])?;
+        for row in rows {
+            let rating_level = row.get::<_, i16>(1);
+            let counts = VoteCounts::new(row.get::<_, i32>(2) as i64, row.get::<_, i32>(3) as i64);
+            aggregates.entry(row.get::<_, i32>(0)).or_default().add(scheme.label(rating_bucket::level_rating(rating_level)), counts);
+        }
+    } else {
+        // buckets do not match rating levels, so aggregate from the rating of each vote (except banned users)
+        let query = match rating_policy {
+            RatingPolicy::Snapshot => "SELECT editorial_id, votes.rating, COUNT(*) FILTER (WHERE score = 1), COUNT(*) FILTER (WHERE score = -1) FROM votes JOIN users ON users.id = votes.user_id WHERE editorial_id = ANY($1) AND NOT users.banned GROUP BY editorial_id, votes.rating".to_string(),
+            RatingPolicy::Latest => format!("SELECT editorial_id, {LATEST_RATING_SQL}, COUNT(*) FILTER (WHERE score = 1), COUNT(*) FILTER (WHERE score = -1) FROM votes JOIN users ON users.id = votes.user_id JOIN editorials ON editorials.id = votes.editorial_id WHERE editorial_id = ANY($1) AND NOT users.banned GROUP BY editorial_id, {LATEST_RATING_SQL}"),
+        };
+        let rows = client.query(&query, &[&editorial_ids])?;
+        for row in rows {
+            let rating = row.get::<_, Option<i16>>(1);
+            let counts = VoteCounts::new(row.get::<_, i64>(2), row.get::<_, i64>(3));
+            aggregates.entry(row.get::<_, i32>(0)).or_default().add(scheme.label(rating), counts);
+        }
+    }
+
+    // solvers only
+    let rows = client.query("SELECT editorial_id, COUNT(*) FILTER (WHERE score = 1), COUNT(*) FILTER (WHERE score = -1) FROM votes JOIN users ON users.id = votes.user_id WHERE editorial_id = ANY($1) AND attempt = $2 AND NOT users.banned GROUP BY editorial_id", &[&editorial_ids, &Attempt::Accepted.as_i16()])?;
+    for row in rows {
+        aggregates.entry(row.get::<_, i32>(0)).or_default().solver_counts = VoteCounts::new(row.get::<_, i64>(1), row.get::<_, i64>(2));
+    }
+
+    // tags
+    let rows = client.query("SELECT editorial_id, tag, COUNT(*) FROM votes JOIN users ON users.id = votes.user_id, UNNEST(votes.tags) AS tag WHERE editorial_id = ANY($1) AND NOT users.banned GROUP BY editorial_id, tag", &[&editorial_ids])?;
+    for row in rows {
+        aggregates.entry(row.get::<_, i32>(0)).or_default().tag_counts.insert(row.get::<_, String>(1), row.get::<_, i64>(2));
+    }
+
+    // error reports
+    let rows = client.query("SELECT editorial_id, COUNT(*) FROM error_reports JOIN users ON users.id = error_reports.user_id WHERE editorial_id = ANY($1) AND NOT users.banned GROUP BY editorial_id", &[&editorial_ids])?;
+    for row in rows {
+        aggregates.entry(row.get::<_, i32>(0)).or_default().error_reports = row.get::<_, i64>(1);
+    }
+
+    // criteria (bucketed by the rating of the vote)
+    let rating = match rating_policy {
+        RatingPolicy::Snapshot => "votes.rating",
+        RatingPolicy::Latest => LATEST_RATING_SQL,
+    };
+    let rows = client.query(&format!("SELECT criterion_scores.editorial_id, criterion, {rating}, SUM(criterion_scores.score), COUNT(*) FROM criterion_scores JOIN votes ON votes.user_id = criterion_scores.user_id AND votes.editorial_id = criterion_scores.editorial_id JOIN users ON users.id = votes.user_id JOIN editorials ON editorials.id = votes.editorial_id WHERE criterion_scores.editorial_id = ANY($1) AND NOT users.banned GROUP BY criterion_scores.editorial_id, criterion, {rating}"), &[&editorial_ids])?;
+    for row in rows {
+        let stats = CriterionStats { sum: row.get::<_, i64>(3), count: row.get::<_, i64>(4) };
+        aggregates.entry(row.get::<_, i32>(0)).or_default().add_criterion(scheme.label(row.get::<_, Option<i16>>(2)), row.get::<_, String>(1), stats);
+    }
+    Ok(aggregates)
+}
+
+/// subtract votes of the user (only on the editorial if given) from `vote_temp` (votes of banned users are not in `vote_temp`)
+pub fn subtract_votes(client: &mut impl postgres::GenericClient, user_id: i32, editorial_id: Option<i32>) -> Result<(), postgres::Error> {
+    client.execute("UPDATE vote_temp SET score = vote_temp.score - CAST(votes.score AS INTEGER), up = vote_temp.up - CAST(votes.score = 1 AS INTEGER), down = vote_temp.down - CAST(votes.score = -1 AS INTEGER) FROM votes WHERE votes.user_id = $1 AND ($2::INTEGER IS NULL OR votes.editorial_id = $2) AND vote_temp.editorial_id = votes.editorial_id AND vote_temp.rating_level = votes.rating_level AND NOT EXISTS (SELECT 1 FROM users WHERE users.id = votes.user_id AND users.banned)", &[&user_id, &editorial_id])?;
+    Ok(())
+}
+
+/// add votes of the user (only on the editorial if given) to `vote_temp` unless the user is banned
+pub fn add_votes(client: &mut impl postgres::GenericClient, user_id: i32, editorial_id: Option<i32>) -> Result<(), postgres::Error> {
+    client.execute("INSERT INTO vote_temp(editorial_id, rating_level, score, up, down) SELECT editorial_id, rating_level, score, CAST(score = 1 AS INTEGER), CAST(score = -1 AS INTEGER) FROM votes WHERE user_id = $1 AND ($2::INTEGER IS NULL OR editorial_id = $2) AND NOT EXISTS (SELECT 1 FROM users WHERE users.id = votes.user_id AND users.banned) ON CONFLICT (editorial_id, rating_level) DO UPDATE SET score = vote_temp.score + EXCLUDED.score, up = vote_temp.up + EXCLUDED.up, down = vote_temp.down + EXCLUDED.down", &[&user_id, &editorial_id])?;
+    Ok(())
+}
+
+/// rebuild `vote_temp` of the editorial from `votes`
+pub fn rebuild_editorial_vote_temp(client: &mut impl postgres::GenericClient, editorial_id: i32) -> Result<(), postgres::Error> {
+    client.execute("DELETE FROM vote_temp WHERE editorial_id = $1", &[&editorial_id])?;
+    client.execute("INSERT INTO vote_temp(editorial_id, rating_level, score, up, down) SELECT editorial_id, rating_level, SUM(score), COUNT(*) FILTER (WHERE score = 1), COUNT(*) FILTER (WHERE score = -1) FROM votes JOIN users ON users.id = votes.user_id WHERE editorial_id = $1 AND NOT users.banned GROUP BY editorial_id, rating_level", &[&editorial_id])?;
+    Ok(())
+}
+
+/// move votes of the user to the buckets of the latest ratings (for `RatingPolicy::Latest`)
+pub fn rebucket_user_votes(client: &mut impl postgres::GenericClient, user_id: i32) -> Result<(), postgres::Error> {
+    let latest_level = format!("COALESCE({LATEST_RATING_SQL} / 100, $2::SMALLINT)");
+    if client.query_opt(&format!("SELECT 1 FROM votes JOIN users ON users.id = votes.user_id JOIN editorials ON editorials.id = votes.editorial_id WHERE votes.user_id = $1 AND votes.rating_level <> {latest_level} LIMIT 1"), &[&user_id, &rating_bucket::UNRATED_LEVEL])?.is_none() {
+        // already in the buckets
+        return Ok(());
+    }
+    subtract_votes(client, user_id, None)?;
+    client.execute(&format!("UPDATE votes SET rating_level = {latest_level} FROM users, editorials WHERE votes.user_id = $1 AND users.id = votes.user_id AND editorials.id = votes.editorial_id"), &[&user_id, &rating_bucket::UNRATED_LEVEL])?;
+    add_votes(client, user_id, None)
+}
+
+/// recompute the bucket of every vote by the rating policy and rebuild `vote_temp` from `votes` (run in a transaction)
+pub fn rebuild_vote_temp(tx: &mut impl postgres::GenericClient) -> Result<(), Box<dyn std::error::Error>> {
+    match config::rating_policy()? {
+        RatingPolicy::Snapshot => tx.execute("UPDATE votes SET rating_level = COALESCE(rating / 100, $1::SMALLINT)", &[&rating_bucket::UNRATED_LEVEL])?,
+        RatingPolicy::Latest => tx.execute(&format!("UPDATE votes SET rating_level = COALESCE({LATEST_RATING_SQL} / 100, $1::SMALLINT) FROM users, editorials WHERE users.id = votes.user_id AND editorials.id = votes.editorial_id"), &[&rating_bucket::UNRATED_LEVEL])?,
+    };
+    tx.execute("DELETE FROM vote_temp", &[])?;
+    tx.execute("INSERT INTO vote_temp(editorial_id, rating_level, score, up, down) SELECT editorial_id, rating_level, SUM(score), COUNT(*) FILTER (WHERE score = 1), COUNT(*) FILTER (WHERE score = -1) FROM votes JOIN users ON users.id = votes.user_id WHERE NOT users.banned GROUP BY editorial_id, rating_level", &[])?;
+    Ok(())
+}
@@ -1,5 +1,7 @@
 use sha2::{Digest, Sha256};
 
+use crate::metrics;
+
 pub fn validate_atcoder_id(atcoder_id: &str) -> bool {
     regex::Regex::new(r#"^[0-9A-Za-z]{3,16}$"#).unwrap().is_match(atcoder_id)
 }
@@ -11,7 +13,7 @@ fn affiliation_token_hash(time_sec: u64, atcoder_id: &str, salt: &str) -> String
     plaintext.push_str(atcoder_id);
     plaintext.push(':');
     plaintext.push_str(salt);
-    hex::encode(&Sha256::digest(&plaintext.into_bytes()))
+    hex::encode(Sha256::digest(plaintext.into_bytes()))
 }
 
 pub fn create_affiliation_token(time_sec: u64, atcoder_id: &str) -> Result<String, Box<dyn std::error::Error>> {
@@ -31,7 +33,7 @@ pub fn validate_affiliation_token(atcoder_id: &str, affiliation_token: &str) ->
     let mut split = affiliation_token.split("-");
     let time_str = split.next().unwrap();
     let hash_orig = split.next().unwrap();
-    let time_sec = u64::from_str_radix(&time_str, 16)?;
+    let time_sec = u64::from_str_radix(time_str, 16)?;
     let created_time = std::time::SystemTime::UNIX_EPOCH.checked_add(std::time::Duration::from_secs(time_sec)).ok_or_else::<String, _>(|| "affiliation_token invalid time".into() )?;
     let current_time = std::time::SystemTime::now();
     if current_time.duration_since(created_time)? > std::time::Duration::from_secs(60 * 60) {
@@ -53,7 +55,7 @@ fn token_hash(time_sec: u64, atcoder_id: &str, user_id: i32, salt: &str) -> Stri
     plaintext.push_str(&user_id.to_string());
     plaintext.push(':');
     plaintext.push_str(salt);
-    hex::encode(&Sha256::digest(&plaintext.into_bytes()))
+    hex::encode(Sha256::digest(plaintext.into_bytes()))
 }
 
 pub fn create_token(time_sec: u64, atcoder_id: &str, user_id: i32) -> Result<String, Box<dyn std::error::Error>> {
@@ -83,9 +85,9 @@ pub fn parse_token(token: &str) -> Result<UserToken, Box<dyn std::error::Error>>
     let mut split = token.split("-");
     let time_str = split.next().unwrap();
     let atcoder_id = split.next().unwrap();
-    let user_id = i32::from_str_radix(split.next().unwrap(), 10)?;
+    let user_id = split.next().unwrap().parse::<i32>()?;
     let hash_orig = split.next().unwrap();
-    let time_sec = u64::from_str_radix(&time_str, 16)?;
+    let time_sec = u64::from_str_radix(time_str, 16)?;
     let hash = token_hash(time_sec, atcoder_id, user_id, &salt);
     if hash != hash_orig {
         return Err("invalid token".into());
@@ -98,6 +100,10 @@ pub fn parse_token(token: &str) -> Result<UserToken, Box<dyn std::error::Error>>
 }
 
 pub async fn scrape_affiliation(atcoder_id: &str) -> Result<String, Box<dyn std::error::Error>> {
+    metrics::scrape("scrape_affiliation", fetch_affiliation(atcoder_id)).await
+}
+
+async fn fetch_affiliation(atcoder_id: &str) -> Result<String, Box<dyn std::error::Error>> {
     if !validate_atcoder_id(atcoder_id) {
         return Err("invalid atcoder id".into());
     }
@@ -119,20 +125,55 @@ pub async fn scrape_affiliation(atcoder_id: &str) -> Result<String, Box<dyn std:
     Ok(affiliation.to_string())
 }
 
-pub async fn scrape_editorials(contest: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-    if !regex::Regex::new(r#"^[-\w]+$"#).unwrap().is_match(&contest) {
+pub struct ScrapedEditorial {
+    pub url: String,
+    /// task ID (e.g. `abc123_a`), `None` for the overall editorial
+    pub task: Option<String>,
+    /// AtCoder ID of the writer
+    pub author: Option<String>,
+}
+
+pub async fn scrape_editorials(contest: &str) -> Result<Vec<ScrapedEditorial>, Box<dyn std::error::Error>> {
+    metrics::scrape("scrape_editorials", fetch_editorials(contest)).await
+}
+
+async fn fetch_editorials(contest: &str) -> Result<Vec<ScrapedEditorial>, Box<dyn std::error::Error>> {
+    if !validate_contest(contest) {
         return Err("contest invalid format".into());
     }
     let mut editorials = vec![];
     for lang in &["ja", "en"] {
         let html = reqwest::get(format!("https://atcoder.jp/contests/{contest}/editorial?editorialLang={lang}")).await?.text().await?;
         let document = scraper::Html::parse_document(&html);
-        let selector = scraper::Selector::parse(r#"#main-container a[rel="noopener"]"#)?;
-        editorials.extend(document.select(&selector).filter_map(|link| link.attr("href").and_then(|url| canonicalize_editorial_url(url) ) ));
+        // headings (one per task) and editorial links, in document order
+        let selector = scraper::Selector::parse(r#"#main-container h3, #main-container h4, #main-container a[rel="noopener"]"#)?;
+        let task_selector = scraper::Selector::parse(r#"a[href*="/tasks/"]"#)?;
+        let author_selector = scraper::Selector::parse(r#"a.username[href^="/users/"]"#)?;
+        let mut task = None;
+        for elem in document.select(&selector) {
+            if elem.value().name() == "a" {
+                if let Some(url) = elem.attr("href").and_then(canonicalize_editorial_url) {
+                    // the writer is linked in the same list item (`<li><a href="...">...</a> by <a class="username" href="/users/...">...</a></li>`)
+                    let author = elem.ancestors().filter_map(scraper::ElementRef::wrap).find(|ancestor| ancestor.value().name() == "li" )
+                        .and_then(|item| item.select(&author_selector).next() )
+                        .and_then(|link| link.attr("href") )
+                        .and_then(|href| href.rsplit('/').next() )
+                        .filter(|atcoder_id| validate_atcoder_id(atcoder_id) )
+                        .map(|atcoder_id| atcoder_id.to_string() );
+                    editorials.push(ScrapedEditorial { url, task: task.clone(), author });
+                }
+            } else {
+                task = elem.select(&task_selector).next().and_then(|link| link.attr("href") ).and_then(|href| href.rsplit('/').next() ).map(|task| task.to_string() );
+            }
+        }
     }
     Ok(editorials)
 }
 
+pub fn validate_contest(contest: &str) -> bool {
+    regex::Regex::new(r#"^[-\w]+$"#).unwrap().is_match(contest)
+}
+
 pub fn canonicalize_editorial_url(url: &str) -> Option<String> {
     if url.starts_with("/jump?url=") {
         let encoded = url.split_at(10).1;
@@ -149,20 +190,227 @@ pub fn canonicalize_editorial_url(url: &str) -> Option<String> {
     Some(url.to_string())
 }
 
+pub struct AtCoderContestDetails {
+    pub start_time: std::time::SystemTime,
+    pub end_time: std::time::SystemTime,
+    /// whether the contest is rated by the heuristic rating (stored in `contests.heuristic`)
+    pub heuristic: bool,
+}
+
+/// contests longer than this (practice2, typical90, tessoku-book, ...) are permanent and treated as ended
+const PERMANENT_CONTEST_DURATION: std::time::Duration = std::time::Duration::from_secs(365 * 24 * 60 * 60);
+
+impl AtCoderContestDetails {
+    pub fn has_ended(&self) -> bool {
+        std::time::SystemTime::now() >= self.end_time || self.end_time.duration_since(self.start_time).is_ok_and(|duration| duration > PERMANENT_CONTEST_DURATION )
+    }
+}
+
+pub async fn scrape_contest(contest: &str) -> Result<AtCoderContestDetails, Box<dyn std::error::Error>> {
+    metrics::scrape("scrape_contest", fetch_contest(contest)).await
+}
+
+async fn fetch_contest(contest: &str) -> Result<AtCoderContestDetails, Box<dyn std::error::Error>> {
+    if !validate_contest(contest) {
+        return Err("contest invalid format".into());
+    }
+
+    let html = reqwest::get(format!("https://atcoder.jp/contests/{contest}?lang=en")).await?.error_for_status()?.text().await?;
+    let document = scraper::Html::parse_document(&html);
+    // `Contest Duration: <time>start</time> - <time>end</time>`
+    let selector = scraper::Selector::parse("small.contest-duration time.fixtime-full")?;
+    let mut times = document.select(&selector).filter_map(|time| time.text().next().and_then(parse_time) );
+    let (Some(start_time), Some(end_time)) = (times.next(), times.next()) else {
+        return Err("contest duration not found".into());
+    };
+    // heuristic contests are marked with `Ⓗ` (`<span title="Heuristic">`); AHC is also recognized by the ID in case the mark is missing
+    let heuristic_selector = scraper::Selector::parse(r#"#main-container span[title="Heuristic"]"#)?;
+    let heuristic = document.select(&heuristic_selector).next().is_some() || contest.starts_with("ahc");
+    Ok(AtCoderContestDetails {
+        start_time,
+        end_time,
+        heuristic,
+    })
+}
+
+/// submissions of a user to a task (stored in `votes.attempt`)
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Attempt {
+    NotSubmitted = 0,
+    Submitted = 1,
+    Accepted = 2,
+}
+
+impl Attempt {
+    pub fn as_i16(self) -> i16 {
+        self as i16
+    }
+
+    pub fn from_i16(value: i16) -> Self {
+        match value {
+            2 => Attempt::Accepted,
+            1 => Attempt::Submitted,
+            _ => Attempt::NotSubmitted,
+        }
+    }
+}
+
+/// check the submissions of the user to the task (or to any task of the contest if `None`)
+pub async fn scrape_attempt(contest: &str, task: Option<&str>, atcoder_id: &str) -> Result<Attempt, Box<dyn std::error::Error>> {
+    metrics::scrape("scrape_attempt", fetch_attempt(contest, task, atcoder_id)).await
+}
+
+async fn fetch_attempt(contest: &str, task: Option<&str>, atcoder_id: &str) -> Result<Attempt, Box<dyn std::error::Error>> {
+    if !validate_contest(contest) || !task.is_none_or(validate_contest) {
+        return Err("contest invalid format".into());
+    }
+    if !validate_atcoder_id(atcoder_id) {
+        return Err("invalid atcoder id".into());
+    }
+
+    let task = task.unwrap_or("");
+    for (status, attempt) in [("AC", Attempt::Accepted), ("", Attempt::Submitted)] {
+        let html = reqwest::get(format!("https://atcoder.jp/contests/{contest}/submissions?f.Task={task}&f.User={atcoder_id}&f.Status={status}&lang=en")).await?.error_for_status()?.text().await?;
+        let document = scraper::Html::parse_document(&html);
+        if document.select(&scraper::Selector::parse("#main-container")?).next().is_none() {
+            return Err("failed to parse submissions page".into());
+        }
+        // the table does not exist if there are no submissions
+        let selector = scraper::Selector::parse("#main-container table > tbody > tr")?;
+        if document.select(&selector).next().is_some() {
+            return Ok(attempt);
+        }
+    }
+    Ok(Attempt::NotSubmitted)
+}
+
 pub struct AtCoderUserDetails {
-    pub rating: i16,
+    /// algorithm rating, `None` if unrated
+    pub rating: Option<i16>,
+    /// heuristic rating, `None` if unrated
+    pub heuristic_rating: Option<i16>,
+    /// number of rated contests (algorithm and heuristic)
+    pub rated_contests: i16,
+    /// end time of the first contest participated in, `None` if never participated
+    pub first_contest_time: Option<std::time::SystemTime>,
 }
+
+impl AtCoderUserDetails {
+    /// rating used for editorials of the contest
+    pub fn rating_for(&self, heuristic: bool) -> Option<i16> {
+        if heuristic { self.heuristic_rating } else { self.rating }
+    }
+}
+
 pub async fn scrape_user(atcoder_id: &str) -> Result<AtCoderUserDetails, Box<dyn std::error::Error>> {
+    metrics::scrape("scrape_user", fetch_user(atcoder_id)).await
+}
+
+async fn fetch_user(atcoder_id: &str) -> Result<AtCoderUserDetails, Box<dyn std::error::Error>> {
     if !validate_atcoder_id(atcoder_id) {
         return Err("invalid atcoder id".into());
     }
 
-    let html = reqwest::get(format!("https://atcoder.jp/users/{atcoder_id}")).await?.text().await?;
-    let document = scraper::Html::parse_document(&html);
-    let selector = scraper::Selector::parse("#main-container > div.row > div.col-md-9.col-sm-12 > table > tbody > tr:nth-child(2) > td > span")?;
-    let rating = document.select(&selector).next().and_then(|elem| elem.text().next() ).and_then(|rating| i16::from_str_radix(rating, 10).ok() ).unwrap_or(0);
-
+    let rating = scrape_rating(atcoder_id, "algo").await?;
+    let heuristic_rating = scrape_rating(atcoder_id, "heuristic").await?;
+    let (algo_rated_contests, algo_first_contest_time) = fetch_contest_history(atcoder_id, "algo").await?;
+    let (heuristic_rated_contests, heuristic_first_contest_time) = fetch_contest_history(atcoder_id, "heuristic").await?;
     Ok(AtCoderUserDetails {
         rating,
+        heuristic_rating,
+        rated_contests: algo_rated_contests + heuristic_rated_contests,
+        first_contest_time: algo_first_contest_time.into_iter().chain(heuristic_first_contest_time).min(),
     })
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContestHistoryEntry {
+    is_rated: bool,
+    end_time: String,
+}
+
+/// returns (number of rated contests, end time of the first contest)
+async fn fetch_contest_history(atcoder_id: &str, contest_type: &str) -> Result<(i16, Option<std::time::SystemTime>), Box<dyn std::error::Error>> {
+    let json = reqwest::get(format!("https://atcoder.jp/users/{atcoder_id}/history/json?contestType={contest_type}")).await?.error_for_status()?.text().await?;
+    let history = serde_json::from_str::<Vec<ContestHistoryEntry>>(&json)?;
+    let rated_contests = history.iter().filter(|entry| entry.is_rated ).count() as i16;
+    let first_contest_time = history.iter().filter_map(|entry| parse_time(&entry.end_time) ).min();
+    Ok((rated_contests, first_contest_time))
+}
+
+/// parse times on AtCoder (e.g. `2021-06-05T22:40:00+09:00`, `2021-06-05 21:00:00+0900`)
+pub fn parse_time(time: &str) -> Option<std::time::SystemTime> {
+    let captures = regex::Regex::new(r#"^(\d{4})-(\d{2})-(\d{2})[T ](\d{2}):(\d{2}):(\d{2})([+-])(\d{2}):?(\d{2})$"#).unwrap().captures(time.trim())?;
+    let num = |i: usize| captures[i].parse::<i64>().ok();
+    let (year, month, day) = (num(1)?, num(2)?, num(3)?);
+    // days from 1970-01-01
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+    let offset = (num(8)? * 60 + num(9)?) * 60 * if &captures[7] == "-" { -1 } else { 1 };
+    let secs = days * 86400 + num(4)? * 3600 + num(5)? * 60 + num(6)? - offset;
+    std::time::SystemTime::UNIX_EPOCH.checked_add(std::time::Duration::from_secs(u64::try_from(secs).ok()?))
+}
+
+async fn scrape_rating(atcoder_id: &str, contest_type: &str) -> Result<Option<i16>, Box<dyn std::error::Error>> {
+    let html = reqwest::get(format!("https://atcoder.jp/users/{atcoder_id}?contestType={contest_type}&lang=en")).await?.error_for_status()?.text().await?;
+    let document = scraper::Html::parse_document(&html);
+    if document.select(&scraper::Selector::parse("#main-container")?).next().is_none() {
+        return Err("failed to parse user page".into());
+    }
+    // the rating table does not exist for unrated users
+    let selector = scraper::Selector::parse("#main-container > div.row > div.col-md-9.col-sm-12 > table > tbody > tr")?;
+    let rating = document.select(&selector)
+        .filter_map(|row| {
+            let mut children = row.child_elements();
+            if children.next()?.text().next()?.trim() == "Rating" {
+                children.next()?.text().map(|text| text.trim() ).find(|text| !text.is_empty() )
+            } else {
+                None
+            }
+        })
+        .next()
+        .map(|rating| rating.parse::<i16>() )
+        .transpose()?;
+    Ok(rating)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unix_time(time: &str) -> Option<u64> {
+        parse_time(time).map(|time| time.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() )
+    }
+
+    #[test]
+    fn parse_time_formats_and_offsets() {
+        let cases = [
+            ("1970-01-01T00:00:00+00:00", 0),
+            // contest pages (`+0900`) and contest histories (`+09:00`)
+            ("2021-06-05T22:40:00+09:00", 1622900400),
+            ("2021-06-05 22:40:00+0900", 1622900400),
+            ("2021-12-31T23:00:00-05:00", 1641009600),
+            // leap days
+            ("2020-02-29 21:00:00+0900", 1582977600),
+            ("2024-03-01T00:00:00+09:00", 1709218800),
+            ("2000-02-29T09:00:00+09:00", 951782400),
+            // 2100 is not a leap year
+            ("2100-03-01T00:00:00+00:00", 4107542400),
+        ];
+        for (time, expected) in cases {
+            assert_eq!(unix_time(time), Some(expected), "{time}");
+        }
+    }
+
+    #[test]
+    fn parse_time_rejects_invalid_times() {
+        assert_eq!(unix_time("2021-06-05"), None);
+        assert_eq!(unix_time("2021-06-05T22:40:00"), None);
+        assert_eq!(unix_time("1969-12-31T23:59:59+00:00"), None);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,14 @@
+use crate::{atcoder_api::{self, UserToken}, error::ApiError};
+
+/// error code of tokens of deleted users
+pub const INVALID_TOKEN: &str = "invalid_token";
+
+/// parse the token and check that the user still exists (tokens are invalidated by `/delete_me`)
+pub fn verify_token(client: &mut impl postgres::GenericClient, token: &str) -> Result<UserToken, Box<dyn std::error::Error>> {
+    let user_token = atcoder_api::parse_token(token)?;
+    let exists = client.query_opt("SELECT 1 FROM users WHERE id = $1 AND atcoder_id = $2", &[&user_token.user_id, &user_token.atcoder_id])?.is_some();
+    if !exists {
+        return Err(ApiError::new(INVALID_TOKEN, "token is no longer valid").into());
+    }
+    Ok(user_token)
+}
\ No newline at end of file
@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UnratedPolicy {
+    /// unrated users can vote (counted in the `unrated` bucket)
+    Allow,
+    /// unrated users cannot vote
+    Deny,
+}
+
+/// `EDITORIAL_VOTING_UNRATED_POLICY`: `allow` (default) or `deny`
+pub fn unrated_policy() -> Result<UnratedPolicy, Box<dyn std::error::Error>> {
+    match std::env::var("EDITORIAL_VOTING_UNRATED_POLICY").as_deref() {
+        Err(_) | Ok("allow") => Ok(UnratedPolicy::Allow),
+        Ok("deny") => Ok(UnratedPolicy::Deny),
+        Ok(_) => Err("invalid EDITORIAL_VOTING_UNRATED_POLICY (allow|deny)".into()),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RatingPolicy {
+    /// aggregate by the rating at the time of voting
+    Snapshot,
+    /// aggregate by the latest rating of the voter
+    Latest,
+}
+
+impl RatingPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RatingPolicy::Snapshot => "snapshot",
+            RatingPolicy::Latest => "latest",
+        }
+    }
+}
+
+/// `EDITORIAL_VOTING_RATING_POLICY`: `snapshot` (default) or `latest`
+pub fn rating_policy() -> Result<RatingPolicy, Box<dyn std::error::Error>> {
+    match std::env::var("EDITORIAL_VOTING_RATING_POLICY").as_deref() {
+        Err(_) | Ok("snapshot") => Ok(RatingPolicy::Snapshot),
+        Ok("latest") => Ok(RatingPolicy::Latest),
+        Ok(_) => Err("invalid EDITORIAL_VOTING_RATING_POLICY (snapshot|latest)".into()),
+    }
+}
+
+/// `EDITORIAL_VOTING_RATING_CACHE_SECS`: cached ratings younger than this are used without scraping (default: 7 days)
+pub fn rating_cache_duration() -> Result<Duration, Box<dyn std::error::Error>> {
+    match std::env::var("EDITORIAL_VOTING_RATING_CACHE_SECS") {
+        Err(_) => Ok(Duration::from_secs(7 * 24 * 60 * 60)),
+        Ok(secs) => Ok(Duration::from_secs(secs.parse().map_err(|_| "invalid EDITORIAL_VOTING_RATING_CACHE_SECS" )?)),
+    }
+}
+
+/// requirements to vote (`None` if not required)
+#[derive(Clone, Default, Debug)]
+pub struct EligibilityRules {
+    /// `EDITORIAL_VOTING_MIN_RATED_CONTESTS`: number of rated contests (algorithm and heuristic)
+    pub min_rated_contests: Option<i16>,
+    /// `EDITORIAL_VOTING_MIN_ACCOUNT_AGE_DAYS`: days since the first contest participated in
+    pub min_account_age: Option<Duration>,
+    /// `EDITORIAL_VOTING_MIN_RATING`: algorithm or heuristic rating
+    pub min_rating: Option<i16>,
+}
+
+impl EligibilityRules {
+    pub fn is_empty(&self) -> bool {
+        self.min_rated_contests.is_none() && self.min_account_age.is_none() && self.min_rating.is_none()
+    }
+}
+
+pub fn eligibility_rules() -> Result<EligibilityRules, Box<dyn std::error::Error>> {
+    fn var<T: std::str::FromStr>(name: &str) -> Result<Option<T>, Box<dyn std::error::Error>> {
+        match std::env::var(name) {
+            Err(_) => Ok(None),
+            Ok(value) => Ok(Some(value.parse().map_err(|_| format!("invalid {name}") )?)),
+        }
+    }
+    Ok(EligibilityRules {
+        min_rated_contests: var("EDITORIAL_VOTING_MIN_RATED_CONTESTS")?,
+        min_account_age: var::<u64>("EDITORIAL_VOTING_MIN_ACCOUNT_AGE_DAYS")?.map(|days| Duration::from_secs(days * 24 * 60 * 60) ),
+        min_rating: var("EDITORIAL_VOTING_MIN_RATING")?,
+    })
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AttemptPolicy {
+    /// submissions are not checked
+    Off,
+    /// submissions are checked and recorded with votes, but not required
+    Record,
+    /// a submission to the task is required to vote
+    RequireSubmission,
+    /// an accepted submission to the task is required to vote
+    RequireAccepted,
+}
+
+/// `EDITORIAL_VOTING_ATTEMPT_POLICY`: `off` (default), `record`, `submitted` or `accepted`
+pub fn attempt_policy() -> Result<AttemptPolicy, Box<dyn std::error::Error>> {
+    match std::env::var("EDITORIAL_VOTING_ATTEMPT_POLICY").as_deref() {
+        Err(_) | Ok("off") => Ok(AttemptPolicy::Off),
+        Ok("record") => Ok(AttemptPolicy::Record),
+        Ok("submitted") => Ok(AttemptPolicy::RequireSubmission),
+        Ok("accepted") => Ok(AttemptPolicy::RequireAccepted),
+        Ok(_) => Err("invalid EDITORIAL_VOTING_ATTEMPT_POLICY (off|record|submitted|accepted)".into()),
+    }
+}
+
+/// `EDITORIAL_VOTING_AUTO_APPROVE_ENDORSEMENTS`: submitted editorials endorsed by this many users are approved without moderators (default: 3, `0` to disable)
+pub fn auto_approve_endorsements() -> Result<Option<i64>, Box<dyn std::error::Error>> {
+    match std::env::var("EDITORIAL_VOTING_AUTO_APPROVE_ENDORSEMENTS") {
+        Err(_) => Ok(Some(3)),
+        Ok(count) => match count.parse::<i64>().map_err(|_| "invalid EDITORIAL_VOTING_AUTO_APPROVE_ENDORSEMENTS" )? {
+            0 => Ok(None),
+            count => Ok(Some(count)),
+        },
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SelfVotePolicy {
+    /// votes on own editorials are rejected with an error
+    Reject,
+    /// votes on own editorials succeed but are not recorded
+    Ignore,
+}
+
+/// `EDITORIAL_VOTING_SELF_VOTE_POLICY`: `reject` (default) or `ignore`
+pub fn self_vote_policy() -> Result<SelfVotePolicy, Box<dyn std::error::Error>> {
+    match std::env::var("EDITORIAL_VOTING_SELF_VOTE_POLICY").as_deref() {
+        Err(_) | Ok("reject") => Ok(SelfVotePolicy::Reject),
+        Ok("ignore") => Ok(SelfVotePolicy::Ignore),
+        Ok(_) => Err("invalid EDITORIAL_VOTING_SELF_VOTE_POLICY (reject|ignore)".into()),
+    }
+}
+
+/// `EDITORIAL_VOTING_HIDE_SCORES_DURING_CONTEST`: `true` to hide scores of editorials until the contest ends (default: `false`)
+pub fn hide_scores_during_contest() -> Result<bool, Box<dyn std::error::Error>> {
+    match std::env::var("EDITORIAL_VOTING_HIDE_SCORES_DURING_CONTEST").as_deref() {
+        Err(_) | Ok("false") => Ok(false),
+        Ok("true") => Ok(true),
+        Ok(_) => Err("invalid EDITORIAL_VOTING_HIDE_SCORES_DURING_CONTEST (true|false)".into()),
+    }
+}
+
+
+/// `EDITORIAL_VOTING_METRICS`: `true` to save metrics to the database after each request, so that `/metrics` covers every instance (default: `false`)
+pub fn metrics_enabled() -> Result<bool, Box<dyn std::error::Error>> {
+    match std::env::var("EDITORIAL_VOTING_METRICS").as_deref() {
+        Err(_) | Ok("false") => Ok(false),
+        Ok("true") => Ok(true),
+        Ok(_) => Err("invalid EDITORIAL_VOTING_METRICS (true|false)".into()),
+    }
+}
\ No newline at end of file
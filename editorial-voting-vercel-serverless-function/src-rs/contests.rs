@@ -0,0 +1,72 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{atcoder_api::{self, AtCoderContestDetails}, config, error::ApiError};
+
+/// cached times of contests not ended yet are used without scraping if younger than this
+const CONTEST_CACHE_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// error code of operations not allowed until the contest ends
+pub const CONTEST_RUNNING: &str = "contest_running";
+
+/// row of `contests`
+struct CachedContest {
+    details: AtCoderContestDetails,
+    /// `None` if scraped before the rating type was recorded
+    heuristic: Option<bool>,
+    last_update: SystemTime,
+}
+
+fn cached_row(client: &mut postgres::Client, contest: &str) -> Result<Option<CachedContest>, Box<dyn std::error::Error>> {
+    Ok(client.query_opt("SELECT start_time, end_time, heuristic, last_update FROM contests WHERE contest = $1", &[&contest])?.map(|row| {
+        let heuristic = row.get::<_, Option<bool>>(2);
+        CachedContest {
+            details: AtCoderContestDetails {
+                start_time: row.get::<_, SystemTime>(0),
+                end_time: row.get::<_, SystemTime>(1),
+                heuristic: heuristic.unwrap_or(false),
+            },
+            heuristic,
+            last_update: row.get::<_, SystemTime>(3),
+        }
+    }))
+}
+
+/// get the cached contest without scraping (`None` if it has never been scraped), for read-only endpoints
+pub fn cached_contest(client: &mut postgres::Client, contest: &str) -> Result<Option<AtCoderContestDetails>, Box<dyn std::error::Error>> {
+    Ok(cached_row(client, contest)?.map(|cached| cached.details ))
+}
+
+/// get the start and end times and the rating type of the contest, scraping and saving them if the cache is old (called from blocking context)
+pub fn get_contest(client: &mut postgres::Client, contest: &str) -> Result<AtCoderContestDetails, Box<dyn std::error::Error>> {
+    if let Some(cached) = cached_row(client, contest)? {
+        // times and rating types of ended contests do not change
+        if cached.heuristic.is_some() && (cached.details.has_ended() || SystemTime::now().duration_since(cached.last_update)? <= CONTEST_CACHE_DURATION) {
+            return Ok(cached.details);
+        }
+    }
+
+    let details = {
+        let contest = contest.to_string();
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(async move { atcoder_api::scrape_contest(&contest).await })?
+    };
+    client.execute("INSERT INTO contests(contest, start_time, end_time, heuristic, last_update) VALUES($1, $2, $3, $4, $5) ON CONFLICT (contest) DO UPDATE SET start_time = $2, end_time = $3, heuristic = $4, last_update = $5", &[&contest, &details.start_time, &details.end_time, &details.heuristic, &SystemTime::now()])?;
+    Ok(details)
+}
+
+/// error with `CONTEST_RUNNING` if the contest has not ended yet
+pub fn ensure_ended(client: &mut postgres::Client, contest: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if !get_contest(client, contest)?.has_ended() {
+        return Err(ApiError::new(CONTEST_RUNNING, "not available until the contest ends").into());
+    }
+    Ok(())
+}
+
+/// whether scores of editorials of the contest should be hidden now (`EDITORIAL_VOTING_HIDE_SCORES_DURING_CONTEST`)
+///
+/// only the cache is used, so contests never scraped (by a vote) are hidden as well
+pub fn hides_scores(client: &mut postgres::Client, contest: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    Ok(config::hide_scores_during_contest()? && !cached_contest(client, contest)?.is_some_and(|details| details.has_ended() ))
+}
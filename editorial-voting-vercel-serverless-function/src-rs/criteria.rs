@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+/// criteria editorials can be graded on
+pub const CRITERIA: [&str; 3] = ["clarity", "correctness", "depth"];
+
+/// range of scores of each criterion
+pub const MIN_SCORE: i16 = 1;
+pub const MAX_SCORE: i16 = 5;
+
+/// sum and number of scores of a criterion
+#[derive(Clone, Copy, Default, Debug)]
+pub struct CriterionStats {
+    pub sum: i64,
+    pub count: i64,
+}
+
+impl CriterionStats {
+    pub fn add(&mut self, other: CriterionStats) {
+        self.sum += other.sum;
+        self.count += other.count;
+    }
+
+    /// average score (`None` if there are no scores)
+    pub fn average(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum as f64 / self.count as f64 )
+    }
+
+    pub fn summary(&self) -> CriterionSummary {
+        CriterionSummary { average: self.average(), count: self.count }
+    }
+}
+
+/// criterion of responses
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct CriterionSummary {
+    pub average: Option<f64>,
+    pub count: i64,
+}
+
+/// check that all criteria are in `CRITERIA` and all scores are in range
+pub fn validate_scores(scores: &HashMap<String, i16>) -> Result<(), Box<dyn std::error::Error>> {
+    for (criterion, score) in scores {
+        if !CRITERIA.contains(&criterion.as_str()) {
+            return Err(format!("invalid criterion {criterion:?} ({})", CRITERIA.join("|")).into());
+        }
+        if !(MIN_SCORE ..= MAX_SCORE).contains(score) {
+            return Err(format!("score of {criterion} must be between {MIN_SCORE} and {MAX_SCORE}").into());
+        }
+    }
+    Ok(())
+}
+
+pub fn summaries(stats: &HashMap<String, CriterionStats>) -> HashMap<String, CriterionSummary> {
+    stats.iter().map(|(criterion, stats)| (criterion.clone(), stats.summary()) ).collect()
+}
\ No newline at end of file
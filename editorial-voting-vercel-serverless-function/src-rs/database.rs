@@ -1,13 +1,23 @@
-pub async fn with_database<P: 'static + Send, T: 'static + Send>(f: fn(postgres::Client, param: P) -> Result<T, Box<dyn std::error::Error>>, param: P) -> Result<T, String> {
-    tokio::task::spawn_blocking(move || {
+use std::time::Instant;
+
+use crate::{error::ApiError, metrics};
+
+/// run `f` with a connection in a blocking thread (recorded in metrics by the name of `f`, e.g. `vote::proc::use_db`)
+pub async fn with_database<P: 'static + Send, T: 'static + Send, F: FnOnce(postgres::Client, P) -> Result<T, Box<dyn std::error::Error>> + 'static + Send>(f: F, param: P) -> Result<T, ApiError> {
+    let query = std::any::type_name::<F>().trim_start_matches("editorial_voting_vercel_serverless_function::").replace("::{{closure}}", "");
+    let started = Instant::now();
+    let result = tokio::task::spawn_blocking(move || {
         let database_url = std::env::var("EDITORIAL_VOTING_DATABASE_URL").unwrap();
 
-        let mut builder = openssl::ssl::SslConnector::builder(openssl::ssl::SslMethod::tls()).map_err(|e| e.to_string() )?;
-        builder.set_ca_file("/etc/ssl/certs/ca-certificates.crt").map_err(|e| e.to_string() )?;
+        let mut builder = openssl::ssl::SslConnector::builder(openssl::ssl::SslMethod::tls()).map_err(|e| ApiError::from_error(&e) )?;
+        builder.set_ca_file("/etc/ssl/certs/ca-certificates.crt").map_err(|e| ApiError::from_error(&e) )?;
 
         let connector = postgres_openssl::MakeTlsConnector::new(builder.build());
 
-        let client = postgres::Client::connect(&database_url, connector).map_err(|e| format!("{e:?}") )?;
-        f(client, param).map_err(|e| e.to_string() )
-    }).await.map_err(|e| e.to_string() )?
+        let client = postgres::Client::connect(&database_url, connector).map_err(|e| ApiError { code: None, message: format!("{e:?}") } )?;
+        f(client, param).map_err(|e| ApiError::from_error(&*e) )
+    }).await.map_err(|e| ApiError::from_error(&e) ).and_then(|result| result );
+    metrics::inc_counter(metrics::DATABASE_CALLS, &[("query", &query), ("result", if result.is_ok() { "success" } else { "error" })]);
+    metrics::observe_duration(metrics::DATABASE_DURATION, &[("query", &query)], started.elapsed());
+    result
 }
\ No newline at end of file
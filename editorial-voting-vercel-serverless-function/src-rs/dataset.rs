@@ -0,0 +1,552 @@
+use std::{collections::{HashMap, HashSet}, io::{BufRead, Write}, path::Path, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use sha2::{Digest, Sha256};
+
+use crate::{aggregate, editorials};
+
+/// version of the dataset format, increased on incompatible changes
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    Jsonl,
+    Csv,
+}
+
+impl Format {
+    pub fn parse(format: &str) -> Option<Format> {
+        match format {
+            "jsonl" => Some(Format::Jsonl),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Format::Jsonl => "jsonl",
+            Format::Csv => "csv",
+        }
+    }
+}
+
+/// what to export
+#[derive(Clone, Debug)]
+pub enum ExportMode {
+    /// visible editorials and their aggregates
+    Public,
+    /// also votes, with the voters replaced by pseudonyms derived from the salt
+    PublicWithVotes { salt: String },
+    /// all users, editorials and votes with AtCoder IDs, which can be imported by `import_dataset`
+    Backup,
+}
+
+/// `meta.json`
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct Meta {
+    pub schema_version: u32,
+    pub format: String,
+    pub exported_at: u64,
+    /// names of the tables in the dataset (`<table>.jsonl` or `<table>.csv`)
+    pub tables: Vec<String>,
+    /// whether the dataset is a backup (`user` of votes are AtCoder IDs)
+    #[serde(default)]
+    pub backup: bool,
+}
+
+/// `users` table (backups only)
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct UserRecord {
+    /// `users.id`, kept so that tokens issued before the restore stay valid (`None` in backups without it)
+    pub id: Option<i32>,
+    pub atcoder_id: String,
+    pub rating: Option<i16>,
+    pub heuristic_rating: Option<i16>,
+    pub rated_contests: Option<i16>,
+    /// UNIX time (seconds)
+    pub first_contest_time: Option<u64>,
+    pub created_at: Option<u64>,
+    pub banned: bool,
+    pub role: String,
+}
+
+/// `editorials` table: editorials with their total votes (hidden and pending editorials are only in backups)
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct EditorialRecord {
+    pub editorial: String,
+    pub contest: Option<String>,
+    pub task: Option<String>,
+    pub author: Option<String>,
+    pub score: i64,
+    pub up: i64,
+    pub down: i64,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default = "approved")]
+    pub status: String,
+}
+
+fn approved() -> String {
+    "approved".to_string()
+}
+
+/// `editorial_levels` table: votes on editorials by rating level (`rating / 100`, -1 for unrated)
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct EditorialLevelRecord {
+    pub editorial: String,
+    pub rating_level: i16,
+    pub score: i64,
+    pub up: i64,
+    pub down: i64,
+}
+
+/// `votes` table: each vote, with the voter replaced by a pseudonym (AtCoder ID in backups)
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct VoteRecord {
+    pub user: String,
+    pub editorial: String,
+    pub score: i16,
+    /// rating at the time of voting (backups only, `None` otherwise so that voters cannot be identified by their rating histories)
+    pub rating: Option<i16>,
+    pub rating_level: i16,
+    /// 0 none, 1 submitted, 2 accepted, `None` if not checked
+    pub attempt: Option<i16>,
+    /// UNIX time (seconds), rounded down to the day unless backup
+    pub voted_at: Option<u64>,
+    pub tags: Vec<String>,
+}
+
+/// type of CSV columns
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColumnType {
+    Text,
+    Number,
+    Boolean,
+    /// list of texts joined with `;`
+    List,
+}
+
+use ColumnType::{Boolean, List, Number, Text};
+
+pub const USER_COLUMNS: [(&str, ColumnType); 9] = [("id", Number), ("atcoder_id", Text), ("rating", Number), ("heuristic_rating", Number), ("rated_contests", Number), ("first_contest_time", Number), ("created_at", Number), ("banned", Boolean), ("role", Text)];
+pub const EDITORIAL_COLUMNS: [(&str, ColumnType); 9] = [("editorial", Text), ("contest", Text), ("task", Text), ("author", Text), ("score", Number), ("up", Number), ("down", Number), ("hidden", Boolean), ("status", Text)];
+pub const EDITORIAL_LEVEL_COLUMNS: [(&str, ColumnType); 5] = [("editorial", Text), ("rating_level", Number), ("score", Number), ("up", Number), ("down", Number)];
+pub const VOTE_COLUMNS: [(&str, ColumnType); 8] = [("user", Text), ("editorial", Text), ("score", Number), ("rating", Number), ("rating_level", Number), ("attempt", Number), ("voted_at", Number), ("tags", List)];
+
+#[derive(Default, Debug)]
+pub struct Dataset {
+    /// `None` unless the dataset is a backup
+    pub users: Option<Vec<UserRecord>>,
+    pub editorials: Vec<EditorialRecord>,
+    pub editorial_levels: Vec<EditorialLevelRecord>,
+    /// `None` if votes are not exported
+    pub votes: Option<Vec<VoteRecord>>,
+}
+
+#[derive(Default, Debug)]
+pub struct ImportSummary {
+    pub users: usize,
+    pub editorials: usize,
+    pub votes: usize,
+}
+
+/// stable pseudonym of the user (same for the same salt)
+pub fn pseudonym(user_id: i32, salt: &str) -> String {
+    hex::encode(Sha256::digest(format!("{salt}:{user_id}").into_bytes()))[.. 16].to_string()
+}
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+fn unix_time(time: Option<SystemTime>) -> Option<u64> {
+    time.and_then(|time| time.duration_since(UNIX_EPOCH).ok() ).map(|duration| duration.as_secs() )
+}
+
+fn system_time(secs: Option<u64>) -> Option<SystemTime> {
+    secs.and_then(|secs| UNIX_EPOCH.checked_add(Duration::from_secs(secs)) )
+}
+
+/// read the dataset from the database (votes of banned users are excluded unless backup)
+pub fn read_dataset(client: &mut postgres::Client, mode: &ExportMode) -> Result<Dataset, Box<dyn std::error::Error>> {
+    let backup = matches!(mode, ExportMode::Backup);
+    let visible = if backup { "TRUE" } else { editorials::VISIBLE_SQL };
+    let mut dataset = Dataset::default();
+
+    if backup {
+        let rows = client.query("SELECT atcoder_id, rating, heuristic_rating, rated_contests, first_contest_time, created_at, banned, role, id FROM users ORDER BY id", &[])?;
+        dataset.users = Some(rows.into_iter().map(|row| UserRecord {
+            id: row.get(8),
+            atcoder_id: row.get(0),
+            rating: row.get(1),
+            heuristic_rating: row.get(2),
+            rated_contests: row.get(3),
+            first_contest_time: unix_time(row.get(4)),
+            created_at: unix_time(row.get(5)),
+            banned: row.get(6),
+            role: row.get(7),
+        }).collect());
+    }
+
+    let rows = client.query(&format!("SELECT editorial, contest, task, author, COALESCE(SUM(vote_temp.score), 0)::BIGINT, COALESCE(SUM(up), 0)::BIGINT, COALESCE(SUM(down), 0)::BIGINT, hidden, status FROM editorials LEFT JOIN vote_temp ON vote_temp.editorial_id = editorials.id WHERE {visible} GROUP BY editorials.id ORDER BY editorials.id"), &[])?;
+    dataset.editorials = rows.into_iter().map(|row| EditorialRecord {
+        editorial: row.get(0),
+        contest: row.get(1),
+        task: row.get(2),
+        author: row.get(3),
+        score: row.get(4),
+        up: row.get(5),
+        down: row.get(6),
+        hidden: row.get(7),
+        status: row.get(8),
+    }).collect();
+
+    let rows = client.query(&format!("SELECT editorial, rating_level, vote_temp.score, up, down FROM vote_temp JOIN editorials ON editorials.id = vote_temp.editorial_id WHERE {visible} AND (up > 0 OR down > 0) ORDER BY editorials.id, rating_level"), &[])?;
+    dataset.editorial_levels = rows.into_iter().map(|row| EditorialLevelRecord {
+        editorial: row.get(0),
+        rating_level: row.get(1),
+        score: row.get::<_, i32>(2) as i64,
+        up: row.get::<_, i32>(3) as i64,
+        down: row.get::<_, i32>(4) as i64,
+    }).collect();
+
+    let user = match mode {
+        ExportMode::Public => return Ok(dataset),
+        ExportMode::PublicWithVotes { salt } => Box::new(|row: &postgres::Row| pseudonym(row.get(0), salt) ) as Box<dyn Fn(&postgres::Row) -> String>,
+        ExportMode::Backup => Box::new(|row: &postgres::Row| row.get(1) ),
+    };
+    let banned = if backup { "TRUE" } else { "NOT users.banned" };
+    let rows = client.query(&format!("SELECT votes.user_id, users.atcoder_id, editorial, votes.score, votes.rating, votes.rating_level, votes.attempt, votes.voted_at, votes.tags FROM votes JOIN users ON users.id = votes.user_id JOIN editorials ON editorials.id = votes.editorial_id WHERE {banned} AND {visible} ORDER BY editorials.id, votes.voted_at NULLS FIRST"), &[])?;
+    dataset.votes = Some(rows.iter().map(|row| VoteRecord {
+        user: user(row),
+        editorial: row.get(2),
+        score: row.get(3),
+        rating: if backup { row.get(4) } else { None },
+        rating_level: row.get(5),
+        attempt: row.get(6),
+        voted_at: unix_time(row.get(7)).map(|secs| if backup { secs } else { secs - secs % SECONDS_PER_DAY } ),
+        tags: row.get(8),
+    }).collect());
+    Ok(dataset)
+}
+
+/// write the dataset to the directory as `meta.json` and a file for each table
+pub fn write_dataset(dir: &Path, format: Format, dataset: &Dataset) -> Result<Meta, Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(dir)?;
+    let mut tables = vec![];
+    if let Some(users) = dataset.users.as_ref() {
+        write_table(dir, "users", format, &USER_COLUMNS, users)?;
+        tables.push("users".to_string());
+    }
+    write_table(dir, "editorials", format, &EDITORIAL_COLUMNS, &dataset.editorials)?;
+    tables.push("editorials".to_string());
+    write_table(dir, "editorial_levels", format, &EDITORIAL_LEVEL_COLUMNS, &dataset.editorial_levels)?;
+    tables.push("editorial_levels".to_string());
+    if let Some(votes) = dataset.votes.as_ref() {
+        write_table(dir, "votes", format, &VOTE_COLUMNS, votes)?;
+        tables.push("votes".to_string());
+    }
+
+    let meta = Meta {
+        schema_version: SCHEMA_VERSION,
+        format: format.as_str().to_string(),
+        exported_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        tables,
+        backup: dataset.users.is_some(),
+    };
+    std::fs::write(dir.join("meta.json"), serde_json::to_string_pretty(&meta)?)?;
+    Ok(meta)
+}
+
+fn write_table<T: serde::Serialize>(dir: &Path, name: &str, format: Format, columns: &[(&str, ColumnType)], records: &[T]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(dir.join(format!("{name}.{}", format.as_str())))?);
+    match format {
+        Format::Jsonl => {
+            for record in records {
+                writeln!(file, "{}", serde_json::to_string(record)?)?;
+            }
+        }
+        Format::Csv => write_csv(&mut file, columns, records)?,
+    }
+    file.flush()?;
+    Ok(())
+}
+
+/// write the records as CSV with a header
+fn write_csv<T: serde::Serialize>(out: &mut impl Write, columns: &[(&str, ColumnType)], records: &[T]) -> Result<(), Box<dyn std::error::Error>> {
+    writeln!(out, "{}", columns.iter().map(|(column, _)| *column ).collect::<Vec<_>>().join(","))?;
+    for record in records {
+        let value = serde_json::to_value(record)?;
+        let fields = columns.iter().map(|(column, _)| csv_field(&value[column]) ).collect::<Vec<_>>();
+        writeln!(out, "{}", fields.join(","))?;
+    }
+    Ok(())
+}
+
+/// CSV field of the value (`null` is empty, arrays are joined with `;`)
+fn csv_field(value: &serde_json::Value) -> String {
+    let text = match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(values) => values.iter().map(|value| value.as_str().map_or_else(|| value.to_string(), |text| text.to_string() ) ).collect::<Vec<_>>().join(";"),
+        value => value.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+/// read a dataset written by `write_dataset`
+pub fn load_dataset(dir: &Path) -> Result<(Meta, Dataset), Box<dyn std::error::Error>> {
+    let meta = serde_json::from_str::<Meta>(&std::fs::read_to_string(dir.join("meta.json"))?)?;
+    if meta.schema_version != SCHEMA_VERSION {
+        return Err(format!("unsupported schema version {} (expected {SCHEMA_VERSION})", meta.schema_version).into());
+    }
+    let format = Format::parse(&meta.format).ok_or("invalid format in meta.json")?;
+    let has_table = |name: &str| meta.tables.iter().any(|table| table == name );
+
+    let dataset = Dataset {
+        users: if has_table("users") { Some(read_table(dir, "users", format, &USER_COLUMNS)?) } else { None },
+        editorials: read_table(dir, "editorials", format, &EDITORIAL_COLUMNS)?,
+        editorial_levels: if has_table("editorial_levels") { read_table(dir, "editorial_levels", format, &EDITORIAL_LEVEL_COLUMNS)? } else { vec![] },
+        votes: if has_table("votes") { Some(read_table(dir, "votes", format, &VOTE_COLUMNS)?) } else { None },
+    };
+    Ok((meta, dataset))
+}
+
+fn read_table<T: serde::de::DeserializeOwned>(dir: &Path, name: &str, format: Format, columns: &[(&str, ColumnType)]) -> Result<Vec<T>, Box<dyn std::error::Error>> {
+    let path = dir.join(format!("{name}.{}", format.as_str()));
+    let file = std::io::BufReader::new(std::fs::File::open(&path)?);
+    let mut records = vec![];
+    match format {
+        Format::Jsonl => {
+            for (index, line) in file.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                records.push(serde_json::from_str(&line).map_err(|e| format!("{}:{}: {e}", path.display(), index + 1) )?);
+            }
+        }
+        Format::Csv => records = read_csv(&std::io::read_to_string(file)?, columns).map_err(|e| format!("{}:{e}", path.display()) )?,
+    }
+    Ok(records)
+}
+
+/// read records from CSV with a header (errors are prefixed with the row number)
+fn read_csv<T: serde::de::DeserializeOwned>(text: &str, columns: &[(&str, ColumnType)]) -> Result<Vec<T>, String> {
+    let mut rows = parse_csv(text).into_iter();
+    let header = rows.next().ok_or(" missing header")?;
+    let mut records = vec![];
+    for (index, row) in rows.enumerate() {
+        let mut object = serde_json::Map::new();
+        for (column, column_type) in columns {
+            let Some(position) = header.iter().position(|name| name == column ) else {
+                continue;
+            };
+            let text = row.get(position).map(|text| text.as_str() ).unwrap_or("");
+            object.insert(column.to_string(), csv_value(text, *column_type).ok_or_else(|| format!("{}: invalid {column}", index + 2) )?);
+        }
+        records.push(serde_json::from_value(serde_json::Value::Object(object)).map_err(|e| format!("{}: {e}", index + 2) )?);
+    }
+    Ok(records)
+}
+
+/// value of a CSV field (`None` if invalid)
+fn csv_value(text: &str, column_type: ColumnType) -> Option<serde_json::Value> {
+    if text.is_empty() && column_type != List {
+        return Some(serde_json::Value::Null);
+    }
+    Some(match column_type {
+        Text => serde_json::Value::String(text.to_string()),
+        Number => serde_json::Value::Number(text.parse::<i64>().ok()?.into()),
+        Boolean => serde_json::Value::Bool(text.parse().ok()?),
+        List => serde_json::Value::Array(text.split(';').filter(|item| !item.is_empty() ).map(|item| serde_json::Value::String(item.to_string()) ).collect()),
+    })
+}
+
+/// split CSV into rows of fields (fields may be quoted with `"`, blank lines are skipped)
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                row.push(std::mem::take(&mut field));
+                if row != [""] {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.clear();
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// check that every vote refers to a user and an editorial in the dataset or the database
+fn validate_references(client: &mut impl postgres::GenericClient, dataset: &Dataset) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(votes) = dataset.votes.as_ref() else {
+        return Ok(());
+    };
+    let mut users = dataset.users.iter().flatten().map(|user| user.atcoder_id.clone() ).collect::<HashSet<_>>();
+    let mut editorials = dataset.editorials.iter().map(|editorial| editorial.editorial.clone() ).collect::<HashSet<_>>();
+    let missing_users = votes.iter().map(|vote| vote.user.clone() ).filter(|user| !users.contains(user) ).collect::<Vec<_>>();
+    users.extend(client.query("SELECT atcoder_id FROM users WHERE atcoder_id = ANY($1)", &[&missing_users])?.into_iter().map(|row| row.get::<_, String>(0) ));
+    let missing_editorials = votes.iter().map(|vote| vote.editorial.clone() ).filter(|editorial| !editorials.contains(editorial) ).collect::<Vec<_>>();
+    editorials.extend(client.query("SELECT editorial FROM editorials WHERE editorial = ANY($1)", &[&missing_editorials])?.into_iter().map(|row| row.get::<_, String>(0) ));
+
+    let mut errors = vec![];
+    for (index, vote) in votes.iter().enumerate() {
+        if !users.contains(&vote.user) {
+            errors.push(format!("vote {}: unknown user {}", index + 1, vote.user));
+        }
+        if !editorials.contains(&vote.editorial) {
+            errors.push(format!("vote {}: unknown editorial {}", index + 1, vote.editorial));
+        }
+        if vote.score != 1 && vote.score != -1 {
+            errors.push(format!("vote {}: invalid score {}", index + 1, vote.score));
+        }
+    }
+    if !errors.is_empty() {
+        let count = errors.len();
+        errors.truncate(10);
+        return Err(format!("{count} invalid references:\n{}", errors.join("\n")).into());
+    }
+    Ok(())
+}
+
+/// import users, editorials and votes of a backup into a database without users and votes, and rebuild `vote_temp`
+///
+/// existing rows with the same keys are replaced, so importing the same backup again does not change the database
+///
+/// backups are partial (criterion scores, error reports, endorsements, submitters, vote reports, the moderation log and the time of the last rating update are not included), so restoring over a live database with users not in the backup is rejected
+pub fn import_dataset(client: &mut postgres::Client, meta: &Meta, dataset: &Dataset) -> Result<ImportSummary, Box<dyn std::error::Error>> {
+    if !meta.backup {
+        return Err("only backups can be imported (export with --backup)".into());
+    }
+    let mut summary = ImportSummary::default();
+    let mut tx = client.transaction()?;
+    let atcoder_ids = dataset.users.iter().flatten().map(|user| user.atcoder_id.clone() ).collect::<Vec<_>>();
+    if let Some(row) = tx.query_opt("SELECT atcoder_id FROM users WHERE atcoder_id <> ALL($1) LIMIT 1", &[&atcoder_ids])? {
+        return Err(format!("user {} is not in the backup (backups can only be restored into a database without other users)", row.get::<_, &str>(0)).into());
+    }
+    validate_references(&mut tx, dataset)?;
+
+    // ids of the backup are kept so that tokens stay valid
+    let statement = tx.prepare("INSERT INTO users(id, atcoder_id, rating, heuristic_rating, rated_contests, first_contest_time, created_at, banned, role) VALUES(COALESCE($9, nextval(pg_get_serial_sequence('users', 'id'))::INTEGER), $1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT (atcoder_id) DO UPDATE SET rating = $2, heuristic_rating = $3, rated_contests = $4, first_contest_time = $5, created_at = $6, banned = $7, role = $8")?;
+    for user in dataset.users.iter().flatten() {
+        tx.execute(&statement, &[&user.atcoder_id, &user.rating, &user.heuristic_rating, &user.rated_contests, &system_time(user.first_contest_time), &system_time(user.created_at), &user.banned, &user.role, &user.id])?;
+        summary.users += 1;
+    }
+    tx.execute("SELECT setval(pg_get_serial_sequence('users', 'id'), GREATEST((SELECT MAX(id) FROM users), 1))", &[])?;
+
+    let statement = tx.prepare("INSERT INTO editorials(editorial, contest, task, author, hidden, status) VALUES($1, $2, $3, $4, $5, $6) ON CONFLICT (editorial) DO UPDATE SET contest = COALESCE($2, editorials.contest), task = COALESCE($3, editorials.task), author = COALESCE($4, editorials.author), hidden = $5, status = $6")?;
+    for editorial in &dataset.editorials {
+        tx.execute(&statement, &[&editorial.editorial, &editorial.contest, &editorial.task, &editorial.author, &editorial.hidden, &editorial.status])?;
+        summary.editorials += 1;
+    }
+
+    if let Some(votes) = dataset.votes.as_ref() {
+        let user_ids = tx.query("SELECT atcoder_id, id FROM users", &[])?.into_iter().map(|row| (row.get::<_, String>(0), row.get::<_, i32>(1)) ).collect::<HashMap<_, _>>();
+        let editorial_ids = tx.query("SELECT editorial, id FROM editorials", &[])?.into_iter().map(|row| (row.get::<_, String>(0), row.get::<_, i32>(1)) ).collect::<HashMap<_, _>>();
+        let statement = tx.prepare("INSERT INTO votes(user_id, editorial_id, score, rating, rating_level, attempt, voted_at, tags) VALUES($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT (user_id, editorial_id) DO UPDATE SET score = $3, rating = $4, rating_level = $5, attempt = $6, voted_at = $7, tags = $8")?;
+        for vote in votes {
+            tx.execute(&statement, &[&user_ids[&vote.user], &editorial_ids[&vote.editorial], &vote.score, &vote.rating, &vote.rating_level, &vote.attempt, &system_time(vote.voted_at), &vote.tags])?;
+            summary.votes += 1;
+        }
+    }
+    aggregate::rebuild_vote_temp(&mut tx)?;
+    tx.commit()?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vote(user: &str, rating: Option<i16>, tags: &[&str]) -> VoteRecord {
+        VoteRecord {
+            user: user.to_string(),
+            editorial: "https://atcoder.jp/contests/abc123/editorial/1".to_string(),
+            score: 1,
+            rating,
+            rating_level: rating.map_or(-1, |rating| rating / 100 ),
+            attempt: None,
+            voted_at: Some(1700000000),
+            tags: tags.iter().map(|tag| tag.to_string() ).collect(),
+        }
+    }
+
+    fn round_trip<T: serde::Serialize + serde::de::DeserializeOwned>(columns: &[(&str, ColumnType)], records: &[T]) -> Vec<T> {
+        let mut out = vec![];
+        write_csv(&mut out, columns, records).unwrap();
+        read_csv(&String::from_utf8(out).unwrap(), columns).unwrap()
+    }
+
+    #[test]
+    fn csv_round_trip_special_characters() {
+        let records = ["a,b", "say \"hi\"", "line\nbreak", "crlf\r\nbreak", "\"\",\n"].map(|user| vote(user, Some(1834), &["clear"]) );
+        let read = round_trip(&VOTE_COLUMNS, &records);
+        assert_eq!(read.len(), records.len());
+        for (read, record) in read.iter().zip(&records) {
+            assert_eq!(read.user, record.user);
+            assert_eq!(read.rating, Some(1834));
+            assert_eq!(read.tags, ["clear"]);
+        }
+    }
+
+    #[test]
+    fn csv_round_trip_empty_and_null() {
+        let read = round_trip(&VOTE_COLUMNS, &[vote("user", None, &[]), vote("other", None, &["clear", "wrong"])]);
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].rating, None);
+        assert_eq!(read[0].rating_level, -1);
+        assert_eq!(read[0].attempt, None);
+        assert!(read[0].tags.is_empty());
+        assert_eq!(read[1].tags, ["clear", "wrong"]);
+    }
+
+    #[test]
+    fn csv_round_trip_users() {
+        let user = UserRecord { id: Some(3), atcoder_id: "tourist".to_string(), rating: None, heuristic_rating: Some(2000), rated_contests: Some(0), first_contest_time: None, created_at: Some(1700000000), banned: true, role: "voter".to_string() };
+        let read = round_trip(&USER_COLUMNS, std::slice::from_ref(&user));
+        assert_eq!(read.len(), 1);
+        assert_eq!((read[0].id, read[0].rating, read[0].heuristic_rating, read[0].first_contest_time, read[0].banned), (Some(3), None, Some(2000), None, true));
+    }
+
+    #[test]
+    fn parse_csv_blank_lines() {
+        assert_eq!(parse_csv("a,b\n1,2\n\n"), [["a", "b"], ["1", "2"]]);
+        assert_eq!(parse_csv("a,b\r\n1,2\r\n\r\n3,4"), [["a", "b"], ["1", "2"], ["3", "4"]]);
+        assert_eq!(parse_csv("a,b\n\"x\ny\",\n"), [["a", "b"], ["x\ny", ""]]);
+        assert!(read_csv::<VoteRecord>("", &VOTE_COLUMNS).is_err());
+    }
+
+    #[test]
+    fn csv_value_types() {
+        assert_eq!(csv_value("", Number), Some(serde_json::Value::Null));
+        assert_eq!(csv_value("-1", Number), Some(serde_json::json!(-1)));
+        assert_eq!(csv_value("x", Number), None);
+        assert_eq!(csv_value("true", Boolean), Some(serde_json::json!(true)));
+        assert_eq!(csv_value("", List), Some(serde_json::json!([])));
+        assert_eq!(csv_field(&serde_json::json!(["a", "b"])), "a;b");
+        assert_eq!(csv_field(&serde_json::Value::Null), "");
+    }
+}
\ No newline at end of file
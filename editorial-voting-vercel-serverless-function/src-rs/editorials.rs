@@ -0,0 +1,92 @@
+use std::time::SystemTime;
+
+use crate::{atcoder_api, config, contests};
+
+/// SQL condition of editorials returned by the endpoints (joined as `editorials`), which are not hidden by moderators nor pending
+pub const VISIBLE_SQL: &str = "(NOT editorials.hidden AND editorials.status = 'approved')";
+
+pub struct SubmissionResult {
+    /// `pending` or `approved`
+    pub status: &'static str,
+    /// number of users who submitted or endorsed the editorial
+    pub endorsements: i64,
+}
+
+pub struct PendingEditorial {
+    pub editorial: String,
+    pub contest: Option<String>,
+    pub task: Option<String>,
+    pub endorsements: i64,
+}
+
+/// register all editorials from the contest editorial page, approving submitted ones found there (called from blocking context)
+pub fn register_contest_editorials(client: &mut postgres::Client, contest: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let contest = contest.to_string();
+    let editorials = {
+        let contest = contest.clone();
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(async move { atcoder_api::scrape_editorials(&contest).await })?
+    };
+    let statement = client.prepare("INSERT INTO editorials(editorial, contest, task, author) VALUES($1, $2, $3, $4) ON CONFLICT (editorial) DO UPDATE SET contest = COALESCE(editorials.contest, $2), task = COALESCE(editorials.task, $3), author = COALESCE(editorials.author, $4), status = CASE WHEN editorials.status = 'pending' THEN 'approved' ELSE editorials.status END")?;
+    for editorial in editorials {
+        client.execute(&statement, &[&editorial.url, &contest, &editorial.task, &editorial.author])?;
+    }
+    Ok(())
+}
+
+/// submit an editorial not linked from the contest editorial page, or endorse it if already submitted
+///
+/// the editorial is approved when `config::auto_approve_endorsements()` users endorsed it
+pub fn submit_editorial(client: &mut postgres::Client, user_id: i32, contest: &str, task: Option<&str>, editorial: &str) -> Result<SubmissionResult, Box<dyn std::error::Error>> {
+    if !atcoder_api::validate_contest(contest) || !task.is_none_or(atcoder_api::validate_contest) {
+        return Err("contest invalid format".into());
+    }
+    // tasks are named `<contest>_<x>`, except ones shared with another contest, which are accepted once an editorial of the contest is registered for them
+    if let Some(task) = task {
+        if !task.starts_with(&format!("{contest}_")) && client.query_opt("SELECT 1 FROM editorials WHERE contest = $1 AND task = $2 LIMIT 1", &[&contest, &task])?.is_none() {
+            return Err("task is not in the contest".into());
+        }
+    }
+    // fails if the contest does not exist
+    contests::get_contest(client, contest)?;
+    let Some(editorial_url) = atcoder_api::canonicalize_editorial_url(editorial).filter(|url| url.starts_with("https://") || url.starts_with("http://") ) else {
+        return Err("invalid editorial URL".into());
+    };
+
+    let mut tx = client.transaction()?;
+    tx.execute("INSERT INTO editorials(editorial, contest, task, status, submitted_by) VALUES($1, $2, $3, 'pending', $4) ON CONFLICT (editorial) DO NOTHING", &[&editorial_url, &contest, &task, &user_id])?;
+    let row = tx.query_one("SELECT id, contest, status, hidden FROM editorials WHERE editorial = $1 FOR UPDATE", &[&editorial_url])?;
+    let editorial_id = row.get::<_, i32>(0);
+    if row.get::<_, bool>(3) || row.get::<_, &str>(2) == "rejected" {
+        return Err("editorial was rejected".into());
+    }
+    if row.get::<_, &str>(2) == "approved" {
+        return Err("editorial is already registered".into());
+    }
+    if row.get::<_, Option<&str>>(1).is_some_and(|registered| registered != contest ) {
+        return Err("editorial is submitted for another contest".into());
+    }
+
+    tx.execute("INSERT INTO editorial_endorsements(editorial_id, user_id, created_at) VALUES($1, $2, $3) ON CONFLICT DO NOTHING", &[&editorial_id, &user_id, &SystemTime::now()])?;
+    let endorsements = tx.query_one("SELECT COUNT(*) FROM editorial_endorsements WHERE editorial_id = $1", &[&editorial_id])?.get::<_, i64>(0);
+    let mut status = "pending";
+    if config::auto_approve_endorsements()?.is_some_and(|required| endorsements >= required ) {
+        tx.execute("UPDATE editorials SET status = 'approved' WHERE id = $1", &[&editorial_id])?;
+        status = "approved";
+    }
+    tx.commit()?;
+    Ok(SubmissionResult { status, endorsements })
+}
+
+/// submitted editorials waiting for approval, ordered by the number of endorsements
+pub fn pending_editorials(client: &mut impl postgres::GenericClient, contest: Option<&str>) -> Result<Vec<PendingEditorial>, postgres::Error> {
+    let rows = client.query("SELECT editorial, contest, task, (SELECT COUNT(*) FROM editorial_endorsements WHERE editorial_id = editorials.id) AS endorsements FROM editorials WHERE status = 'pending' AND NOT hidden AND ($1::TEXT IS NULL OR contest = $1) ORDER BY endorsements DESC, id LIMIT 256", &[&contest])?;
+    Ok(rows.into_iter().map(|row| PendingEditorial {
+        editorial: row.get(0),
+        contest: row.get(1),
+        task: row.get(2),
+        endorsements: row.get(3),
+    }).collect())
+}
\ No newline at end of file
@@ -0,0 +1,93 @@
+use std::time::{Duration, SystemTime};
+
+use crate::{atcoder_api::{self, AtCoderUserDetails, Attempt}, config::{self, AttemptPolicy, EligibilityRules}, error::ApiError, user_rating};
+
+/// error code of users who do not satisfy the eligibility rules
+pub const INELIGIBLE: &str = "ineligible";
+
+/// error code of users who have not attempted the task required by `config::attempt_policy()`
+pub const NOT_ATTEMPTED: &str = "not_attempted";
+
+/// time a scraped attempt other than accepted is reused (accepted ones never change)
+const ATTEMPT_CACHE_DURATION: Duration = Duration::from_secs(10 * 60);
+
+/// check that the user satisfies `config::eligibility_rules()` (called from blocking context)
+pub fn check_eligibility(client: &mut postgres::Client, user_id: i32, atcoder_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let rules = config::eligibility_rules()?;
+    if rules.is_empty() {
+        return Ok(());
+    }
+    let details = user_rating::get_details(client, user_id, atcoder_id)?;
+    check_rules(&rules, &details)
+}
+
+/// check the eligibility rules for a user not registered yet, returning the scraped details to be saved after registering (called from blocking context)
+pub fn check_new_user(atcoder_id: &str) -> Result<Option<AtCoderUserDetails>, Box<dyn std::error::Error>> {
+    let rules = config::eligibility_rules()?;
+    if rules.is_empty() {
+        return Ok(None);
+    }
+    let details = user_rating::scrape_details(atcoder_id)?;
+    check_rules(&rules, &details)?;
+    Ok(Some(details))
+}
+
+/// submissions of the user to the task, from the votes or `attempts` if accepted or checked recently
+fn get_attempt(client: &mut postgres::Client, user_id: i32, contest: &str, task: Option<&str>, atcoder_id: &str) -> Result<Attempt, Box<dyn std::error::Error>> {
+    let accepted = Attempt::Accepted.as_i16();
+    if client.query_opt("SELECT 1 FROM votes JOIN editorials ON editorials.id = votes.editorial_id WHERE votes.user_id = $1 AND votes.attempt = $2 AND editorials.contest = $3 AND ($4::TEXT IS NULL OR editorials.task = $4) LIMIT 1", &[&user_id, &accepted, &contest, &task])?.is_some() {
+        return Ok(Attempt::Accepted);
+    }
+    let task = task.unwrap_or("");
+    if let Some(row) = client.query_opt("SELECT attempt, checked_at FROM attempts WHERE user_id = $1 AND contest = $2 AND task = $3", &[&user_id, &contest, &task])? {
+        let attempt = Attempt::from_i16(row.get(0));
+        if attempt == Attempt::Accepted || SystemTime::now().duration_since(row.get::<_, SystemTime>(1))? <= ATTEMPT_CACHE_DURATION {
+            return Ok(attempt);
+        }
+    }
+    let attempt = {
+        let (contest, task, atcoder_id) = (contest.to_string(), (!task.is_empty()).then(|| task.to_string() ), atcoder_id.to_string());
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(async move { atcoder_api::scrape_attempt(&contest, task.as_deref(), &atcoder_id).await })?
+    };
+    client.execute("INSERT INTO attempts(user_id, contest, task, attempt, checked_at) VALUES($1, $2, $3, $4, $5) ON CONFLICT (user_id, contest, task) DO UPDATE SET attempt = $4, checked_at = $5", &[&user_id, &contest, &task, &attempt.as_i16(), &SystemTime::now()])?;
+    Ok(attempt)
+}
+
+fn check_rules(rules: &EligibilityRules, details: &AtCoderUserDetails) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(min_rated_contests) = rules.min_rated_contests {
+        if details.rated_contests < min_rated_contests {
+            return Err(ApiError::new(INELIGIBLE, format!("at least {min_rated_contests} rated contests are required to vote")).into());
+        }
+    }
+    if let Some(min_account_age) = rules.min_account_age {
+        let old_enough = details.first_contest_time.is_some_and(|time| SystemTime::now().duration_since(time).is_ok_and(|age| age >= min_account_age ) );
+        if !old_enough {
+            return Err(ApiError::new(INELIGIBLE, format!("at least {} days since the first contest are required to vote", min_account_age.as_secs() / (24 * 60 * 60))).into());
+        }
+    }
+    if let Some(min_rating) = rules.min_rating {
+        if details.rating.max(details.heuristic_rating).is_none_or(|rating| rating < min_rating ) {
+            return Err(ApiError::new(INELIGIBLE, format!("rating of at least {min_rating} is required to vote")).into());
+        }
+    }
+    Ok(())
+}
+
+/// check the submissions of the user to the task by `config::attempt_policy()`, `None` if not checked (called from blocking context)
+pub fn check_attempt(client: &mut postgres::Client, user_id: i32, contest: &str, task: Option<&str>, atcoder_id: &str) -> Result<Option<Attempt>, Box<dyn std::error::Error>> {
+    let required = match config::attempt_policy()? {
+        AttemptPolicy::Off => return Ok(None),
+        AttemptPolicy::Record => Attempt::NotSubmitted,
+        AttemptPolicy::RequireSubmission => Attempt::Submitted,
+        AttemptPolicy::RequireAccepted => Attempt::Accepted,
+    };
+    let attempt = get_attempt(client, user_id, contest, task, atcoder_id)?;
+    if attempt < required {
+        let message = if required == Attempt::Accepted { "an accepted submission to the task is required to vote" } else { "a submission to the task is required to vote" };
+        return Err(ApiError::new(NOT_ATTEMPTED, message).into());
+    }
+    Ok(Some(attempt))
+}
@@ -0,0 +1,32 @@
+use std::fmt;
+
+/// error returned to clients, with an optional machine-readable `code`
+#[derive(Debug)]
+pub struct ApiError {
+    pub code: Option<&'static str>,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self { code: Some(code), message: message.into() }
+    }
+
+    /// keep the code if the error is an `ApiError`, otherwise only the message
+    pub fn from_error(error: &(dyn std::error::Error + 'static)) -> Self {
+        Self { code: error_code(error), message: error.to_string() }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// `code` of the error response
+pub fn error_code(error: &(dyn std::error::Error + 'static)) -> Option<&'static str> {
+    error.downcast_ref::<ApiError>().and_then(|error| error.code )
+}
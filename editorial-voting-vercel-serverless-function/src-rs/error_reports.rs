@@ -0,0 +1,58 @@
+use std::time::SystemTime;
+
+use crate::{atcoder_api, editorials, moderation::{self, Role}};
+
+/// maximum length of descriptions of error reports
+pub const MAX_DESCRIPTION_LENGTH: usize = 1000;
+
+/// maximum length of counterexamples of error reports
+pub const MAX_COUNTEREXAMPLE_LENGTH: usize = 10000;
+
+pub struct ErrorReport {
+    pub editorial: String,
+    /// AtCoder ID of the user who reported the error, `None` unless listed by a moderator
+    pub reporter: Option<String>,
+    pub description: String,
+    pub counterexample: Option<String>,
+    pub created_at: SystemTime,
+}
+
+/// report a mistake in the editorial (replaces the previous report of the user on the editorial)
+pub fn report_error(client: &mut postgres::Client, user_id: i32, editorial: &str, description: &str, counterexample: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    if description.trim().is_empty() || description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        return Err(format!("description must be 1 to {MAX_DESCRIPTION_LENGTH} characters").into());
+    }
+    if counterexample.is_some_and(|counterexample| counterexample.chars().count() > MAX_COUNTEREXAMPLE_LENGTH ) {
+        return Err(format!("counterexample must be at most {MAX_COUNTEREXAMPLE_LENGTH} characters").into());
+    }
+    let Some(editorial_url) = atcoder_api::canonicalize_editorial_url(editorial) else {
+        return Err("invalid editorial URL".into());
+    };
+    let Some(row) = client.query_opt(&format!("SELECT id FROM editorials WHERE editorial = $1 AND {}", editorials::VISIBLE_SQL), &[&editorial_url])? else {
+        return Err("editorial not found".into());
+    };
+    let editorial_id = row.get::<_, i32>(0);
+    client.execute("INSERT INTO error_reports(editorial_id, user_id, description, counterexample, created_at) VALUES($1, $2, $3, $4, $5) ON CONFLICT (editorial_id, user_id) DO UPDATE SET description = $3, counterexample = $4, created_at = $5", &[&editorial_id, &user_id, &description, &counterexample, &SystemTime::now()])?;
+    Ok(())
+}
+
+/// error reports on the editorial (or on all editorials if `None`) from the newest
+///
+/// moderators can list all reports, authors only those on their own editorials without the reporters
+pub fn list_error_reports(client: &mut postgres::Client, user_id: i32, atcoder_id: &str, editorial: Option<&str>, limit: i64) -> Result<Vec<ErrorReport>, Box<dyn std::error::Error>> {
+    let editorial_url = editorial.map(|editorial| atcoder_api::canonicalize_editorial_url(editorial).ok_or("invalid editorial URL") ).transpose()?;
+    let is_author = match editorial_url.as_ref() {
+        Some(editorial_url) => client.query_opt("SELECT author FROM editorials WHERE editorial = $1", &[&editorial_url])?.and_then(|row| row.get::<_, Option<String>>(0) ).is_some_and(|author| author.eq_ignore_ascii_case(atcoder_id) ),
+        None => false,
+    };
+    let role = moderation::require_role(client, user_id, if is_author { Role::Voter } else { Role::Moderator })?;
+
+    let rows = client.query("SELECT editorials.editorial, users.atcoder_id, description, counterexample, error_reports.created_at FROM error_reports JOIN editorials ON editorials.id = error_reports.editorial_id JOIN users ON users.id = error_reports.user_id WHERE ($1::TEXT IS NULL OR editorials.editorial = $1) ORDER BY error_reports.created_at DESC LIMIT $2", &[&editorial_url, &limit])?;
+    Ok(rows.into_iter().map(|row| ErrorReport {
+        editorial: row.get(0),
+        reporter: (role >= Role::Moderator).then(|| row.get(1) ),
+        description: row.get(2),
+        counterexample: row.get(3),
+        created_at: row.get(4),
+    }).collect())
+}
\ No newline at end of file
@@ -1,2 +1,21 @@
+pub mod aggregate;
 pub mod atcoder_api;
-pub mod database;
\ No newline at end of file
+pub mod auth;
+pub mod config;
+pub mod contests;
+pub mod criteria;
+pub mod dataset;
+pub mod database;
+pub mod editorials;
+pub mod eligibility;
+pub mod error;
+pub mod error_reports;
+pub mod metrics;
+pub mod moderation;
+pub mod personal_data;
+pub mod ranking;
+pub mod rating_bucket;
+pub mod rating_refresh;
+pub mod user_rating;
+pub mod vote_analysis;
+pub mod vote_tags;
\ No newline at end of file
@@ -0,0 +1,213 @@
+//! Counters and histograms, rendered in the Prometheus text format.
+//!
+//! Each serverless function runs in its own process, so the values recorded in a process are added to `metric_samples` by `flush`
+//! after each request (if `EDITORIAL_VOTING_METRICS` is `true`) and `/metrics` renders the table.
+
+use std::{collections::BTreeMap, fmt::Write, future::Future, sync::Mutex, time::{Duration, Instant}};
+
+use crate::{config, database};
+
+pub const REQUESTS: &str = "editorial_voting_requests_total";
+pub const REQUEST_DURATION: &str = "editorial_voting_request_duration_seconds";
+pub const VOTES: &str = "editorial_voting_votes_total";
+pub const SCRAPES: &str = "editorial_voting_scrapes_total";
+pub const SCRAPE_DURATION: &str = "editorial_voting_scrape_duration_seconds";
+pub const DATABASE_CALLS: &str = "editorial_voting_database_calls_total";
+pub const DATABASE_DURATION: &str = "editorial_voting_database_duration_seconds";
+
+/// (name, type, help) of the metrics, in the order they are rendered
+const METRICS: [(&str, &str, &str); 7] = [
+    (REQUESTS, "counter", "Requests by endpoint, status and error code."),
+    (REQUEST_DURATION, "histogram", "Time to handle requests by endpoint."),
+    (VOTES, "counter", "Accepted votes by vote (up, down or none)."),
+    (SCRAPES, "counter", "Scrapes of AtCoder by function and result."),
+    (SCRAPE_DURATION, "histogram", "Time of scrapes of AtCoder by function."),
+    (DATABASE_CALLS, "counter", "Database queries (functions passed to database::with_database) by query and result."),
+    (DATABASE_DURATION, "histogram", "Time of database queries by query."),
+];
+
+/// upper bounds (seconds) of the buckets of the duration histograms
+const DURATION_BUCKETS: [f64; 12] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+type Key = (&'static str, Vec<(&'static str, String)>);
+
+#[derive(Default)]
+struct Histogram {
+    /// number of observations in each bucket (not cumulative)
+    buckets: [u64; DURATION_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+struct Registry {
+    counters: BTreeMap<Key, u64>,
+    histograms: BTreeMap<Key, Histogram>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry { counters: BTreeMap::new(), histograms: BTreeMap::new() });
+
+fn with_registry<T>(f: impl FnOnce(&mut Registry) -> T) -> T {
+    // metrics are still usable after a panic while recording
+    let mut registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner() );
+    f(&mut registry)
+}
+
+fn key(name: &'static str, labels: &[(&'static str, &str)]) -> Key {
+    (name, labels.iter().map(|(label, value)| (*label, value.to_string()) ).collect())
+}
+
+pub fn inc_counter(name: &'static str, labels: &[(&'static str, &str)]) {
+    with_registry(|registry| *registry.counters.entry(key(name, labels)).or_default() += 1 );
+}
+
+pub fn observe_duration(name: &'static str, labels: &[(&'static str, &str)], duration: Duration) {
+    let secs = duration.as_secs_f64();
+    with_registry(|registry| {
+        let histogram = registry.histograms.entry(key(name, labels)).or_default();
+        if let Some(index) = DURATION_BUCKETS.iter().position(|bound| secs <= *bound ) {
+            histogram.buckets[index] += 1;
+        }
+        histogram.count += 1;
+        histogram.sum += secs;
+    });
+}
+
+/// record a handled request (`code` is the error code of the response, if any)
+pub fn record_request(endpoint: &str, started: Instant, status: &str, code: Option<&str>) {
+    inc_counter(REQUESTS, &[("endpoint", endpoint), ("status", status), ("code", code.unwrap_or(""))]);
+    observe_duration(REQUEST_DURATION, &[("endpoint", endpoint)], started.elapsed());
+}
+
+/// record the result and the time of a scrape of AtCoder
+pub async fn scrape<T>(function: &str, future: impl Future<Output = Result<T, Box<dyn std::error::Error>>>) -> Result<T, Box<dyn std::error::Error>> {
+    let started = Instant::now();
+    let result = future.await;
+    inc_counter(SCRAPES, &[("function", function), ("result", if result.is_ok() { "success" } else { "error" })]);
+    observe_duration(SCRAPE_DURATION, &[("function", function)], started.elapsed());
+    result
+}
+
+/// a line of the Prometheus text format
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub metric: String,
+    /// `""` for counters, `"_bucket"`, `"_sum"` or `"_count"` for histograms
+    pub suffix: String,
+    /// labels without braces (e.g. `endpoint="vote"`)
+    pub labels: String,
+    /// upper bound of the histogram bucket, 0 for other samples
+    pub le: f64,
+    pub value: f64,
+}
+
+/// escape a label value
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn format_labels(labels: &[(&'static str, String)]) -> String {
+    labels.iter().map(|(label, value)| format!("{label}=\"{}\"", escape(value)) ).collect::<Vec<_>>().join(",")
+}
+
+fn samples(registry: &Registry) -> Vec<Sample> {
+    let sample = |metric: &str, suffix: &str, labels: &str, le: f64, value: f64| Sample { metric: metric.to_string(), suffix: suffix.to_string(), labels: labels.to_string(), le, value };
+    let mut samples = vec![];
+    for ((name, labels), value) in &registry.counters {
+        samples.push(sample(name, "", &format_labels(labels), 0.0, *value as f64));
+    }
+    for ((name, labels), histogram) in &registry.histograms {
+        let labels = format_labels(labels);
+        let mut cumulative = 0;
+        for (bound, count) in DURATION_BUCKETS.iter().zip(histogram.buckets) {
+            cumulative += count;
+            samples.push(sample(name, "_bucket", &labels, *bound, cumulative as f64));
+        }
+        samples.push(sample(name, "_bucket", &labels, f64::INFINITY, histogram.count as f64));
+        samples.push(sample(name, "_sum", &labels, 0.0, histogram.sum));
+        samples.push(sample(name, "_count", &labels, 0.0, histogram.count as f64));
+    }
+    samples
+}
+
+/// samples recorded in this process since the last call, resetting the values
+pub fn take_samples() -> Vec<Sample> {
+    with_registry(|registry| {
+        let samples = samples(registry);
+        registry.counters.clear();
+        registry.histograms.clear();
+        samples
+    })
+}
+
+/// samples in the Prometheus text format (version 0.0.4)
+pub fn render_samples(samples: &[Sample]) -> String {
+    let mut text = String::new();
+    for (name, metric_type, help) in METRICS {
+        let mut lines = samples.iter().filter(|sample| sample.metric == name ).collect::<Vec<_>>();
+        if lines.is_empty() {
+            continue;
+        }
+        // buckets of the same labels are in ascending order, followed by `_sum` and `_count`
+        let suffix_order = |suffix: &str| ["", "_bucket", "_sum", "_count"].iter().position(|s| *s == suffix );
+        lines.sort_by(|a, b| a.labels.cmp(&b.labels).then(suffix_order(&a.suffix).cmp(&suffix_order(&b.suffix))).then(a.le.total_cmp(&b.le)) );
+        // writing to a String does not fail
+        let _ = writeln!(text, "# HELP {name} {help}\n# TYPE {name} {metric_type}");
+        for sample in lines {
+            let mut labels = sample.labels.clone();
+            if sample.suffix == "_bucket" {
+                let le = if sample.le.is_infinite() { "+Inf".to_string() } else { sample.le.to_string() };
+                labels = if labels.is_empty() { format!("le=\"{le}\"") } else { format!("{labels},le=\"{le}\"") };
+            }
+            let labels = if labels.is_empty() { String::new() } else { format!("{{{labels}}}") };
+            let _ = writeln!(text, "{}{}{labels} {}", sample.metric, sample.suffix, sample.value);
+        }
+    }
+    text
+}
+
+/// metrics recorded in this process in the Prometheus text format
+pub fn render() -> String {
+    render_samples(&with_registry(|registry| samples(registry) ))
+}
+
+/// add the samples to `metric_samples`
+pub fn save_samples(client: &mut postgres::Client, samples: &[Sample]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut tx = client.transaction()?;
+    let statement = tx.prepare("INSERT INTO metric_samples(metric, suffix, labels, le, value) VALUES($1, $2, $3, $4, $5) ON CONFLICT (metric, suffix, labels, le) DO UPDATE SET value = metric_samples.value + $5")?;
+    for sample in samples {
+        tx.execute(&statement, &[&sample.metric, &sample.suffix, &sample.labels, &sample.le, &sample.value])?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// samples accumulated in `metric_samples`
+pub fn load_samples(client: &mut postgres::Client) -> Result<Vec<Sample>, Box<dyn std::error::Error>> {
+    let rows = client.query("SELECT metric, suffix, labels, le, value FROM metric_samples", &[])?;
+    Ok(rows.into_iter().map(|row| Sample {
+        metric: row.get(0),
+        suffix: row.get(1),
+        labels: row.get(2),
+        le: row.get(3),
+        value: row.get(4),
+    }).collect())
+}
+
+/// move the samples recorded in this process to `metric_samples` (does nothing unless `EDITORIAL_VOTING_METRICS` is `true`)
+///
+/// failures are only logged, so that metrics do not break requests
+pub async fn flush() {
+    if !config::metrics_enabled().unwrap_or(false) {
+        return;
+    }
+    let samples = take_samples();
+    if samples.is_empty() {
+        return;
+    }
+    fn use_db(mut client: postgres::Client, samples: Vec<Sample>) -> Result<(), Box<dyn std::error::Error>> {
+        save_samples(&mut client, &samples)
+    }
+    if let Err(e) = database::with_database(use_db, samples).await {
+        tracing::warn!("failed to save metrics: {e}");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,205 @@
+use std::time::SystemTime;
+
+use crate::{aggregate, atcoder_api, editorials, error::ApiError};
+
+/// error code of banned users
+pub const BANNED: &str = "banned";
+
+/// error code of users without the required role
+pub const FORBIDDEN: &str = "forbidden";
+
+/// moderator recorded for operations from the command line
+pub const CLI_MODERATOR: &str = "cli";
+
+/// role of a user (`users.role`), each role has the powers of the lower roles
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Role {
+    Voter,
+    /// can ban users, hide editorials and re-scrape contests
+    Moderator,
+    /// can also merge editorials
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Voter => "voter",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn parse(role: &str) -> Option<Role> {
+        match role {
+            "voter" => Some(Role::Voter),
+            "moderator" => Some(Role::Moderator),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+}
+
+pub struct ModerationLogEntry {
+    /// AtCoder ID of the moderator, or `CLI_MODERATOR`
+    pub moderator: String,
+    /// `ban`, `unban`, `hide`, `unhide`, `merge`, `rescrape`, `role`, `approve` or `reject`
+    pub action: String,
+    /// AtCoder ID of the user, URL of the editorial or contest ID
+    pub target: String,
+    pub reason: String,
+    pub created_at: SystemTime,
+}
+
+/// error with `BANNED` if the user is banned
+pub fn ensure_not_banned(client: &mut impl postgres::GenericClient, user_id: i32) -> Result<(), Box<dyn std::error::Error>> {
+    match client.query_opt("SELECT banned FROM users WHERE id = $1", &[&user_id])? {
+        None => Err("user not found".into()),
+        Some(row) if row.get::<_, bool>(0) => Err(ApiError::new(BANNED, "this user is banned").into()),
+        Some(_) => Ok(()),
+    }
+}
+
+/// error with `FORBIDDEN` unless the user has the role (or a higher one), returns the role of the user
+pub fn require_role(client: &mut impl postgres::GenericClient, user_id: i32, role: Role) -> Result<Role, Box<dyn std::error::Error>> {
+    let Some(row) = client.query_opt("SELECT role, banned FROM users WHERE id = $1", &[&user_id])? else {
+        return Err("user not found".into());
+    };
+    if row.get::<_, bool>(1) {
+        return Err(ApiError::new(BANNED, "this user is banned").into());
+    }
+    let user_role = Role::parse(row.get::<_, &str>(0)).ok_or("invalid role")?;
+    if user_role < role {
+        return Err(ApiError::new(FORBIDDEN, format!("{} role is required", role.as_str())).into());
+    }
+    Ok(user_role)
+}
+
+/// change the role of the user
+pub fn set_role(client: &mut postgres::Client, atcoder_id: &str, role: Role, moderator: &str, reason: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut tx = client.transaction()?;
+    if tx.execute("UPDATE users SET role = $2 WHERE atcoder_id = $1", &[&atcoder_id, &role.as_str()])? == 0 {
+        return Err("user not found".into());
+    }
+    log(&mut tx, moderator, "role", atcoder_id, &format!("{}: {reason}", role.as_str()))?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// ban (or unban) the user, removing their votes from (or adding them back to) `vote_temp`
+///
+/// `moderator_role` is the role of the moderator, who can only ban users with a lower role (`None` for `CLI_MODERATOR`, who can ban anyone)
+pub fn set_banned(client: &mut postgres::Client, atcoder_id: &str, banned: bool, moderator: &str, moderator_role: Option<Role>, reason: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut tx = client.transaction()?;
+    let Some(row) = tx.query_opt("SELECT id, banned, role FROM users WHERE atcoder_id = $1 FOR UPDATE", &[&atcoder_id])? else {
+        return Err("user not found".into());
+    };
+    let user_id = row.get::<_, i32>(0);
+    if let Some(moderator_role) = moderator_role {
+        let user_role = Role::parse(row.get::<_, &str>(2)).ok_or("invalid role")?;
+        if moderator_role <= user_role {
+            return Err(ApiError::new(FORBIDDEN, format!("cannot ban users with {} role", user_role.as_str())).into());
+        }
+    }
+    if row.get::<_, bool>(1) == banned {
+        return Err(if banned { "user is already banned" } else { "user is not banned" }.into());
+    }
+
+    // votes of banned users are not in vote_temp
+    if banned {
+        aggregate::subtract_votes(&mut tx, user_id, None)?;
+        tx.execute("UPDATE users SET banned = TRUE WHERE id = $1", &[&user_id])?;
+    } else {
+        tx.execute("UPDATE users SET banned = FALSE WHERE id = $1", &[&user_id])?;
+        aggregate::add_votes(&mut tx, user_id, None)?;
+    }
+    log(&mut tx, moderator, if banned { "ban" } else { "unban" }, atcoder_id, reason)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// hide (or unhide) the editorial from all endpoints
+pub fn set_hidden(client: &mut postgres::Client, editorial: &str, hidden: bool, moderator: &str, reason: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(editorial_url) = atcoder_api::canonicalize_editorial_url(editorial) else {
+        return Err("invalid editorial URL".into());
+    };
+    let mut tx = client.transaction()?;
+    if tx.execute("UPDATE editorials SET hidden = $2 WHERE editorial = $1", &[&editorial_url, &hidden])? == 0 {
+        return Err("editorial not found".into());
+    }
+    log(&mut tx, moderator, if hidden { "hide" } else { "unhide" }, &editorial_url, reason)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// approve (or reject) the submitted editorial
+pub fn review_editorial(client: &mut postgres::Client, editorial: &str, approved: bool, moderator: &str, reason: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(editorial_url) = atcoder_api::canonicalize_editorial_url(editorial) else {
+        return Err("invalid editorial URL".into());
+    };
+    let mut tx = client.transaction()?;
+    let status = if approved { "approved" } else { "rejected" };
+    if tx.execute("UPDATE editorials SET status = $2 WHERE editorial = $1 AND status = 'pending'", &[&editorial_url, &status])? == 0 {
+        return Err("editorial is not pending".into());
+    }
+    log(&mut tx, moderator, if approved { "approve" } else { "reject" }, &editorial_url, reason)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// move votes, error reports and endorsements on the editorial `from` to `into` and hide `from` (those on both are kept only on `into`)
+pub fn merge_editorials(client: &mut postgres::Client, from: &str, into: &str, moderator: &str, reason: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let (Some(from_url), Some(into_url)) = (atcoder_api::canonicalize_editorial_url(from), atcoder_api::canonicalize_editorial_url(into)) else {
+        return Err("invalid editorial URL".into());
+    };
+    if from_url == into_url {
+        return Err("cannot merge an editorial into itself".into());
+    }
+    let mut tx = client.transaction()?;
+    let mut get_id = |url: &str| -> Result<i32, Box<dyn std::error::Error>> {
+        let row = tx.query_opt("SELECT id FROM editorials WHERE editorial = $1 FOR UPDATE", &[&url])?.ok_or("editorial not found")?;
+        Ok(row.get::<_, i32>(0))
+    };
+    let (from_id, into_id) = (get_id(&from_url)?, get_id(&into_url)?);
+
+    tx.execute("UPDATE votes SET editorial_id = $2 WHERE editorial_id = $1 AND NOT EXISTS (SELECT 1 FROM votes AS other WHERE other.user_id = votes.user_id AND other.editorial_id = $2)", &[&from_id, &into_id])?;
+    tx.execute("DELETE FROM votes WHERE editorial_id = $1", &[&from_id])?;
+    tx.execute("UPDATE error_reports SET editorial_id = $2 WHERE editorial_id = $1 AND NOT EXISTS (SELECT 1 FROM error_reports AS other WHERE other.user_id = error_reports.user_id AND other.editorial_id = $2)", &[&from_id, &into_id])?;
+    tx.execute("DELETE FROM error_reports WHERE editorial_id = $1", &[&from_id])?;
+    tx.execute("UPDATE editorial_endorsements SET editorial_id = $2 WHERE editorial_id = $1 AND NOT EXISTS (SELECT 1 FROM editorial_endorsements AS other WHERE other.user_id = editorial_endorsements.user_id AND other.editorial_id = $2)", &[&from_id, &into_id])?;
+    tx.execute("DELETE FROM editorial_endorsements WHERE editorial_id = $1", &[&from_id])?;
+    aggregate::rebuild_editorial_vote_temp(&mut tx, from_id)?;
+    aggregate::rebuild_editorial_vote_temp(&mut tx, into_id)?;
+    tx.execute("UPDATE editorials SET hidden = TRUE WHERE id = $1", &[&from_id])?;
+    log(&mut tx, moderator, "merge", &from_url, &format!("into {into_url}: {reason}"))?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// register the editorials of the contest again, to pick up editorials added after the first registration (called from blocking context)
+pub fn rescrape_contest(client: &mut postgres::Client, contest: &str, moderator: &str, reason: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if !atcoder_api::validate_contest(contest) {
+        return Err("contest invalid format".into());
+    }
+    editorials::register_contest_editorials(client, contest)?;
+    log(client, moderator, "rescrape", contest, reason)?;
+    Ok(())
+}
+
+/// record an operation of a moderator
+pub fn log(client: &mut impl postgres::GenericClient, moderator: &str, action: &str, target: &str, reason: &str) -> Result<(), postgres::Error> {
+    client.execute("INSERT INTO moderation_log(moderator, action, target, reason, created_at) VALUES($1, $2, $3, $4, $5)", &[&moderator, &action, &target, &reason, &SystemTime::now()])?;
+    Ok(())
+}
+
+/// latest entries of the moderation log
+pub fn recent_log(client: &mut impl postgres::GenericClient, limit: i64) -> Result<Vec<ModerationLogEntry>, postgres::Error> {
+    let rows = client.query("SELECT moderator, action, target, reason, created_at FROM moderation_log ORDER BY id DESC LIMIT $1", &[&limit])?;
+    Ok(rows.into_iter().map(|row| ModerationLogEntry {
+        moderator: row.get(0),
+        action: row.get(1),
+        target: row.get(2),
+        reason: row.get(3),
+        created_at: row.get(4),
+    }).collect())
+}
\ No newline at end of file
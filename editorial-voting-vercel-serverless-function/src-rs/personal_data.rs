@@ -0,0 +1,99 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::aggregate;
+
+/// UNIX time (seconds) of exported times
+fn unix_time(time: Option<SystemTime>) -> Option<u64> {
+    time.and_then(|time| time.duration_since(UNIX_EPOCH).ok() ).map(|duration| duration.as_secs() )
+}
+
+/// everything stored about the user
+pub fn export_user(client: &mut impl postgres::GenericClient, user_id: i32) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let Some(row) = client.query_opt("SELECT atcoder_id, rating, heuristic_rating, rated_contests, first_contest_time, rating_last_update, banned, role, created_at FROM users WHERE id = $1", &[&user_id])? else {
+        return Err("user not found".into());
+    };
+    let atcoder_id = row.get::<_, String>(0);
+    let user = serde_json::json!({
+        "atcoder_id": atcoder_id,
+        "rating": row.get::<_, Option<i16>>(1),
+        "heuristic_rating": row.get::<_, Option<i16>>(2),
+        "rated_contests": row.get::<_, Option<i16>>(3),
+        "first_contest_time": unix_time(row.get(4)),
+        "rating_last_update": unix_time(row.get(5)),
+        "banned": row.get::<_, bool>(6),
+        "role": row.get::<_, String>(7),
+        "created_at": unix_time(row.get(8)),
+    });
+
+    let votes = client.query("SELECT editorials.editorial, editorials.contest, editorials.task, votes.score, votes.rating, votes.attempt, votes.voted_at, votes.tags, (SELECT JSON_OBJECT_AGG(criterion, score) FROM criterion_scores WHERE criterion_scores.user_id = votes.user_id AND criterion_scores.editorial_id = votes.editorial_id) FROM votes JOIN editorials ON editorials.id = votes.editorial_id WHERE votes.user_id = $1 ORDER BY votes.voted_at NULLS FIRST, editorials.id", &[&user_id])?.into_iter().map(|row| serde_json::json!({
+        "editorial": row.get::<_, String>(0),
+        "contest": row.get::<_, Option<String>>(1),
+        "task": row.get::<_, Option<String>>(2),
+        "score": row.get::<_, i16>(3),
+        "rating": row.get::<_, Option<i16>>(4),
+        "attempt": row.get::<_, Option<i16>>(5),
+        "voted_at": unix_time(row.get(6)),
+        "tags": row.get::<_, Vec<String>>(7),
+        "criteria": row.get::<_, Option<serde_json::Value>>(8),
+    })).collect::<Vec<_>>();
+
+    let submitted_editorials = client.query("SELECT editorial, contest, task, status FROM editorials WHERE submitted_by = $1 ORDER BY id", &[&user_id])?.into_iter().map(|row| serde_json::json!({
+        "editorial": row.get::<_, String>(0),
+        "contest": row.get::<_, Option<String>>(1),
+        "task": row.get::<_, Option<String>>(2),
+        "status": row.get::<_, String>(3),
+    })).collect::<Vec<_>>();
+
+    let endorsements = client.query("SELECT editorials.editorial, editorial_endorsements.created_at FROM editorial_endorsements JOIN editorials ON editorials.id = editorial_endorsements.editorial_id WHERE user_id = $1 ORDER BY editorial_endorsements.created_at", &[&user_id])?.into_iter().map(|row| serde_json::json!({
+        "editorial": row.get::<_, String>(0),
+        "created_at": unix_time(row.get(1)),
+    })).collect::<Vec<_>>();
+
+    let error_reports = client.query("SELECT editorials.editorial, description, counterexample, error_reports.created_at FROM error_reports JOIN editorials ON editorials.id = error_reports.editorial_id WHERE user_id = $1 ORDER BY error_reports.created_at", &[&user_id])?.into_iter().map(|row| serde_json::json!({
+        "editorial": row.get::<_, String>(0),
+        "description": row.get::<_, String>(1),
+        "counterexample": row.get::<_, Option<String>>(2),
+        "created_at": unix_time(row.get(3)),
+    })).collect::<Vec<_>>();
+
+    // operations of moderators on the user
+    let moderation_log = client.query("SELECT action, reason, created_at FROM moderation_log WHERE target = $1 AND action IN ('ban', 'unban', 'role') ORDER BY id", &[&atcoder_id])?.into_iter().map(|row| serde_json::json!({
+        "action": row.get::<_, String>(0),
+        "reason": row.get::<_, String>(1),
+        "created_at": unix_time(row.get(2)),
+    })).collect::<Vec<_>>();
+
+    Ok(serde_json::json!({
+        "user": user,
+        "votes": votes,
+        "submitted_editorials": submitted_editorials,
+        "endorsements": endorsements,
+        "error_reports": error_reports,
+        "moderation_log": moderation_log,
+    }))
+}
+
+/// delete the votes and the row of the user, which invalidates their tokens
+///
+/// banned users are kept as a row with only the AtCoder ID and the ban, so that they cannot register again
+pub fn delete_user(client: &mut postgres::Client, user_id: i32) -> Result<(), Box<dyn std::error::Error>> {
+    let mut tx = client.transaction()?;
+    let banned = tx.query_opt("SELECT banned FROM users WHERE id = $1 FOR UPDATE", &[&user_id])?.ok_or("user not found")?.get::<_, bool>(0);
+
+    // votes (criterion_scores are deleted with them)
+    aggregate::subtract_votes(&mut tx, user_id, None)?;
+    tx.execute("DELETE FROM votes WHERE user_id = $1", &[&user_id])?;
+    tx.execute("DELETE FROM editorial_endorsements WHERE user_id = $1", &[&user_id])?;
+    tx.execute("DELETE FROM error_reports WHERE user_id = $1", &[&user_id])?;
+    // submitted editorials are kept without the submitter
+    tx.execute("UPDATE editorials SET submitted_by = NULL WHERE submitted_by = $1", &[&user_id])?;
+    tx.execute("UPDATE vote_reports SET user_ids = ARRAY_REMOVE(user_ids, $1) WHERE $1 = ANY(user_ids)", &[&user_id])?;
+    if banned {
+        tx.execute("DELETE FROM attempts WHERE user_id = $1", &[&user_id])?;
+        tx.execute("UPDATE users SET rating = NULL, heuristic_rating = NULL, rated_contests = NULL, first_contest_time = NULL, rating_last_update = NULL, role = 'voter', created_at = NULL WHERE id = $1", &[&user_id])?;
+    } else {
+        tx.execute("DELETE FROM users WHERE id = $1", &[&user_id])?;
+    }
+    tx.commit()?;
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+/// z value of the 95% confidence interval used by the Wilson score
+const WILSON_Z: f64 = 1.96;
+
+/// prior pseudo-votes of the Bayesian average (equivalent to one up-vote and one down-vote)
+const BAYESIAN_PRIOR_UP: f64 = 1.0;
+const BAYESIAN_PRIOR_DOWN: f64 = 1.0;
+
+/// `composite` parameter of requests: weight of each rating bucket in the composite score
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default, Debug)]
+pub struct CompositeOptions {
+    /// weight of each bucket label (e.g. `{"0-99": 0.5, "gold": 2}`)
+    #[serde(default)]
+    pub weights: HashMap<String, f64>,
+    /// weight of buckets not in `weights` (default: 1)
+    pub default_weight: Option<f64>,
+}
+
+impl CompositeOptions {
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.weights.values().chain(self.default_weight.iter()).any(|weight| !weight.is_finite() || *weight < 0.0 ) {
+            return Err("composite weights must be non-negative numbers".into());
+        }
+        Ok(())
+    }
+
+    fn weight(&self, bucket: &str) -> f64 {
+        self.weights.get(bucket).copied().unwrap_or(self.default_weight.unwrap_or(1.0))
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct VoteCounts {
+    pub up: i64,
+    pub down: i64,
+}
+
+impl VoteCounts {
+    pub fn new(up: i64, down: i64) -> Self {
+        Self { up, down }
+    }
+
+    pub fn score(&self) -> i64 {
+        self.up - self.down
+    }
+
+    pub fn total(&self) -> i64 {
+        self.up + self.down
+    }
+
+    pub fn add(&mut self, other: VoteCounts) {
+        self.up += other.up;
+        self.down += other.down;
+    }
+
+    /// lower bound of the Wilson score interval of the up-vote ratio (0 if there are no votes)
+    pub fn wilson_lower_bound(&self) -> f64 {
+        wilson_lower_bound(self.up, self.down)
+    }
+
+    /// up-vote ratio smoothed towards the prior (0.5 if there are no votes)
+    pub fn bayesian_average(&self) -> f64 {
+        bayesian_average(self.up, self.down)
+    }
+
+    /// net score normalized to -1 .. 1 (`None` if there are no votes)
+    pub fn normalized_score(&self) -> Option<f64> {
+        (self.total() > 0).then(|| self.score() as f64 / self.total() as f64 )
+    }
+}
+
+pub fn wilson_lower_bound(up: i64, down: i64) -> f64 {
+    let n = (up + down) as f64;
+    if n <= 0.0 {
+        return 0.0;
+    }
+    let p = up as f64 / n;
+    let z2 = WILSON_Z * WILSON_Z;
+    let center = p + z2 / (2.0 * n);
+    let margin = WILSON_Z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    ((center - margin) / (1.0 + z2 / n)).max(0.0)
+}
+
+pub fn bayesian_average(up: i64, down: i64) -> f64 {
+    (up as f64 + BAYESIAN_PRIOR_UP) / ((up + down) as f64 + BAYESIAN_PRIOR_UP + BAYESIAN_PRIOR_DOWN)
+}
+
+/// weighted average of the normalized score of each bucket, so that each bucket contributes regardless of its number of votes
+/// (`None` if no bucket with positive weight has votes)
+pub fn composite_score(counts_by_bucket: &HashMap<String, VoteCounts>, options: &CompositeOptions) -> Option<f64> {
+    let mut weighted_sum = 0.0;
+    let mut weight_sum = 0.0;
+    for (bucket, counts) in counts_by_bucket {
+        let Some(normalized) = counts.normalized_score() else {
+            continue;
+        };
+        let weight = options.weight(bucket);
+        weighted_sum += weight * normalized;
+        weight_sum += weight;
+    }
+    (weight_sum > 0.0).then(|| weighted_sum / weight_sum )
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn wilson_lower_bound_without_votes() {
+        assert_close(wilson_lower_bound(0, 0), 0.0);
+    }
+
+    #[test]
+    fn wilson_lower_bound_of_down_votes_only() {
+        assert_close(wilson_lower_bound(0, 1), 0.0);
+        assert_close(wilson_lower_bound(0, 100), 0.0);
+    }
+
+    #[test]
+    fn wilson_lower_bound_grows_with_votes() {
+        // n = 1, p = 1: 1 / (1 + z^2)
+        assert_close(wilson_lower_bound(1, 0), 1.0 / (1.0 + WILSON_Z * WILSON_Z));
+        assert!(wilson_lower_bound(10, 0) > wilson_lower_bound(1, 0));
+        assert!(wilson_lower_bound(100, 0) < 1.0);
+        assert!(wilson_lower_bound(9, 1) < wilson_lower_bound(10, 0));
+    }
+
+    #[test]
+    fn bayesian_average_boundaries() {
+        assert_close(bayesian_average(0, 0), 0.5);
+        assert_close(bayesian_average(0, 2), 0.25);
+        assert_close(bayesian_average(2, 0), 0.75);
+        assert_close(VoteCounts::new(3, 3).bayesian_average(), 0.5);
+    }
+
+    #[test]
+    fn composite_score_without_votes() {
+        let counts = HashMap::from([("gray".to_string(), VoteCounts::default())]);
+        assert_eq!(composite_score(&HashMap::new(), &CompositeOptions::default()), None);
+        assert_eq!(composite_score(&counts, &CompositeOptions::default()), None);
+    }
+
+    #[test]
+    fn composite_score_of_down_votes_only() {
+        let counts = HashMap::from([("gray".to_string(), VoteCounts::new(0, 5)), ("red".to_string(), VoteCounts::new(0, 1))]);
+        assert_close(composite_score(&counts, &CompositeOptions::default()).unwrap(), -1.0);
+    }
+
+    #[test]
+    fn composite_score_weights_buckets_equally_by_default() {
+        // gray: +1 (100 votes), red: -1 (1 vote)
+        let counts = HashMap::from([("gray".to_string(), VoteCounts::new(100, 0)), ("red".to_string(), VoteCounts::new(0, 1))]);
+        assert_close(composite_score(&counts, &CompositeOptions::default()).unwrap(), 0.0);
+
+        let options = CompositeOptions { weights: HashMap::from([("red".to_string(), 3.0)]), default_weight: None };
+        assert_close(composite_score(&counts, &options).unwrap(), -0.5);
+
+        let options = CompositeOptions { weights: HashMap::new(), default_weight: Some(0.0) };
+        assert_eq!(composite_score(&counts, &options), None);
+    }
+
+    #[test]
+    fn composite_options_reject_invalid_weights() {
+        assert!(CompositeOptions { weights: HashMap::from([("red".to_string(), -1.0)]), default_weight: None }.validate().is_err());
+        assert!(CompositeOptions { weights: HashMap::new(), default_weight: Some(f64::NAN) }.validate().is_err());
+        assert!(CompositeOptions::default().validate().is_ok());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,172 @@
+/// AtCoder rating colors (lower bound of each color)
+const COLORS: [(&str, i16); 11] = [
+    ("gray", 0),
+    ("brown", 400),
+    ("green", 800),
+    ("cyan", 1200),
+    ("blue", 1600),
+    ("yellow", 2000),
+    ("orange", 2400),
+    ("red", 2800),
+    ("bronze", 3200),
+    ("silver", 3600),
+    ("gold", 4000),
+];
+
+const MAX_CUSTOM_BUCKETS: usize = 100;
+
+/// `vote_temp.rating_level` of unrated voters
+pub const UNRATED_LEVEL: i16 = -1;
+/// bucket label of unrated voters (in every scheme)
+pub const UNRATED_LABEL: &str = "unrated";
+
+/// `vote_temp.rating_level` of the rating (`None` if unrated)
+pub fn rating_level(rating: Option<i16>) -> i16 {
+    rating.map(|rating| rating / 100 ).unwrap_or(UNRATED_LEVEL)
+}
+
+/// lowest rating of `vote_temp.rating_level` (`None` if unrated)
+pub fn level_rating(rating_level: i16) -> Option<i16> {
+    (rating_level != UNRATED_LEVEL).then(|| rating_level * 100 )
+}
+
+/// `buckets` parameter of requests: `"100"`, `"color"` or a list of `[min, max]` ranges
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum BucketsParam {
+    Named(String),
+    Custom(Vec<(i16, i16)>),
+}
+
+#[derive(Clone, Debug)]
+pub enum BucketScheme {
+    /// 100-point bands (`0-99`, `100-199`, ...)
+    Hundred,
+    /// AtCoder colors (`gray`, `brown`, ..., `red`, `bronze`, `silver`, `gold`)
+    Color,
+    /// custom ranges (`min-max`), ratings outside of the ranges are not counted
+    Custom(Vec<(i16, i16)>),
+}
+
+impl BucketScheme {
+    pub fn from_param(param: Option<&BucketsParam>) -> Result<Self, Box<dyn std::error::Error>> {
+        match param {
+            None => Ok(BucketScheme::Hundred),
+            Some(BucketsParam::Named(name)) => match name.as_str() {
+                "100" => Ok(BucketScheme::Hundred),
+                "color" => Ok(BucketScheme::Color),
+                _ => Err("invalid buckets (\"100\"|\"color\"|Array<[min, max]>)".into()),
+            },
+            Some(BucketsParam::Custom(ranges)) => {
+                if ranges.is_empty() || ranges.len() > MAX_CUSTOM_BUCKETS {
+                    return Err(format!("number of buckets must be between 1 and {MAX_CUSTOM_BUCKETS}").into());
+                }
+                let mut sorted = ranges.clone();
+                sorted.sort();
+                if sorted.iter().any(|&(min, max)| min < 0 || min > max ) || sorted.windows(2).any(|w| w[0].1 >= w[1].0 ) {
+                    return Err("buckets must be non-overlapping ranges of non-negative ratings".into());
+                }
+                Ok(BucketScheme::Custom(sorted))
+            }
+        }
+    }
+
+    /// label of the bucket the rating (`None` if unrated) belongs to
+    pub fn label(&self, rating: Option<i16>) -> Option<String> {
+        let Some(rating) = rating else {
+            return Some(UNRATED_LABEL.to_string());
+        };
+        match self {
+            BucketScheme::Hundred => {
+                let level = rating / 100;
+                Some(format!("{}-{}", level as usize * 100, level as usize * 100 + 99))
+            }
+            BucketScheme::Color => COLORS.iter().rev().find(|&&(_, min)| min <= rating ).map(|&(color, _)| color.to_string() ),
+            BucketScheme::Custom(ranges) => ranges.iter().find(|&&(min, max)| min <= rating && rating <= max ).map(|&(min, max)| format!("{min}-{max}") ),
+        }
+    }
+
+    /// whether every bucket is a union of 100-point rating levels, so that `vote_temp` can be used
+    pub fn is_level_aligned(&self) -> bool {
+        match self {
+            BucketScheme::Hundred | BucketScheme::Color => true,
+            BucketScheme::Custom(ranges) => ranges.iter().all(|&(min, max)| min % 100 == 0 && max % 100 == 99 ),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(ranges: &[(i16, i16)]) -> Result<BucketScheme, Box<dyn std::error::Error>> {
+        BucketScheme::from_param(Some(&BucketsParam::Custom(ranges.to_vec())))
+    }
+
+    #[test]
+    fn from_param_named() {
+        assert!(matches!(BucketScheme::from_param(None).unwrap(), BucketScheme::Hundred));
+        assert!(matches!(BucketScheme::from_param(Some(&BucketsParam::Named("100".to_string()))).unwrap(), BucketScheme::Hundred));
+        assert!(matches!(BucketScheme::from_param(Some(&BucketsParam::Named("color".to_string()))).unwrap(), BucketScheme::Color));
+        assert!(BucketScheme::from_param(Some(&BucketsParam::Named("200".to_string()))).is_err());
+    }
+
+    #[test]
+    fn from_param_custom_edges() {
+        // touching ranges are allowed, overlapping ones are not
+        assert!(custom(&[(0, 1199), (1200, 9999)]).is_ok());
+        assert!(custom(&[(0, 1200), (1200, 9999)]).is_err());
+        assert!(custom(&[(1200, 9999), (0, 1200)]).is_err());
+        assert!(custom(&[(5, 5)]).is_ok());
+        assert!(custom(&[(6, 5)]).is_err());
+        assert!(custom(&[(-1, 5)]).is_err());
+        assert!(custom(&[]).is_err());
+        assert!(custom(&vec![(0, 0); MAX_CUSTOM_BUCKETS + 1]).is_err());
+    }
+
+    #[test]
+    fn label_hundred() {
+        let scheme = BucketScheme::Hundred;
+        assert_eq!(scheme.label(Some(0)).as_deref(), Some("0-99"));
+        assert_eq!(scheme.label(Some(99)).as_deref(), Some("0-99"));
+        assert_eq!(scheme.label(Some(100)).as_deref(), Some("100-199"));
+        assert_eq!(scheme.label(None).as_deref(), Some(UNRATED_LABEL));
+    }
+
+    #[test]
+    fn label_color_edges() {
+        let scheme = BucketScheme::Color;
+        assert_eq!(scheme.label(Some(0)).as_deref(), Some("gray"));
+        assert_eq!(scheme.label(Some(399)).as_deref(), Some("gray"));
+        assert_eq!(scheme.label(Some(400)).as_deref(), Some("brown"));
+        assert_eq!(scheme.label(Some(2799)).as_deref(), Some("orange"));
+        assert_eq!(scheme.label(Some(2800)).as_deref(), Some("red"));
+        assert_eq!(scheme.label(Some(4000)).as_deref(), Some("gold"));
+        assert_eq!(scheme.label(None).as_deref(), Some(UNRATED_LABEL));
+    }
+
+    #[test]
+    fn label_custom_edges() {
+        let scheme = custom(&[(1200, 9999), (0, 1199)]).unwrap();
+        assert_eq!(scheme.label(Some(0)).as_deref(), Some("0-1199"));
+        assert_eq!(scheme.label(Some(1199)).as_deref(), Some("0-1199"));
+        assert_eq!(scheme.label(Some(1200)).as_deref(), Some("1200-9999"));
+        assert_eq!(scheme.label(None).as_deref(), Some(UNRATED_LABEL));
+
+        // ratings outside of the ranges are not counted
+        let scheme = custom(&[(1000, 1999)]).unwrap();
+        assert_eq!(scheme.label(Some(999)), None);
+        assert_eq!(scheme.label(Some(2000)), None);
+        assert!(scheme.is_level_aligned());
+        assert!(!custom(&[(1000, 1998)]).unwrap().is_level_aligned());
+    }
+
+    #[test]
+    fn rating_level_of_unrated() {
+        assert_eq!(rating_level(None), UNRATED_LEVEL);
+        assert_eq!(rating_level(Some(1234)), 12);
+        assert_eq!(level_rating(UNRATED_LEVEL), None);
+        assert_eq!(level_rating(12), Some(1200));
+    }
+}
\ No newline at end of file
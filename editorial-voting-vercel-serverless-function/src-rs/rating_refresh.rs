@@ -0,0 +1,81 @@
+use std::{sync::Arc, time::{Duration, SystemTime}};
+
+use crate::{atcoder_api::{self, AtCoderUserDetails}, database, user_rating};
+
+/// number of requests to AtCoder made by `atcoder_api::scrape_user` (rating pages and contest histories of algorithm and heuristic)
+pub const REQUESTS_PER_USER: u32 = 4;
+
+pub struct RefreshOptions {
+    /// maximum number of users scraped concurrently
+    pub concurrency: usize,
+    /// minimum interval between starting to scrape users (each user takes `REQUESTS_PER_USER` requests)
+    pub interval: Duration,
+    /// ratings fetched more recently than this are not refreshed
+    pub max_age: Duration,
+    /// number of users scraped before saving to the database
+    pub chunk_size: usize,
+}
+
+impl Default for RefreshOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 2,
+            // about one request per second
+            interval: Duration::from_millis(1000) * REQUESTS_PER_USER,
+            max_age: Duration::from_secs(60 * 60),
+            chunk_size: 50,
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct RefreshSummary {
+    pub updated: usize,
+    /// AtCoder IDs of the users whose ratings could not be fetched, with the reasons
+    pub failures: Vec<(String, String)>,
+}
+
+/// refresh ratings of all users who have voted
+pub async fn refresh_voter_ratings(options: &RefreshOptions) -> Result<RefreshSummary, Box<dyn std::error::Error>> {
+    fn get_voters(mut client: postgres::Client, updated_before: SystemTime) -> Result<Vec<(i32, String)>, Box<dyn std::error::Error>> {
+        let rows = client.query("SELECT id, atcoder_id FROM users WHERE EXISTS (SELECT 1 FROM votes WHERE user_id = users.id) AND (rating_last_update IS NULL OR rating_last_update < $1) ORDER BY rating_last_update NULLS FIRST", &[&updated_before])?;
+        Ok(rows.into_iter().map(|row| (row.get::<_, i32>(0), row.get::<_, String>(1)) ).collect())
+    }
+    fn save_ratings(mut client: postgres::Client, ratings: Vec<(i32, AtCoderUserDetails)>) -> Result<(), Box<dyn std::error::Error>> {
+        for (user_id, details) in ratings {
+            user_rating::store_rating(&mut client, user_id, &details)?;
+        }
+        Ok(())
+    }
+
+    let voters = database::with_database(get_voters, SystemTime::now() - options.max_age).await?;
+
+    let mut summary = RefreshSummary::default();
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(options.concurrency.max(1)));
+    let mut interval = tokio::time::interval(options.interval.max(Duration::from_millis(1)));
+    for chunk in voters.chunks(options.chunk_size.max(1)) {
+        // scrape
+        let mut tasks = tokio::task::JoinSet::new();
+        for (user_id, atcoder_id) in chunk.iter().cloned() {
+            interval.tick().await;
+            let permit = semaphore.clone().acquire_owned().await?;
+            tasks.spawn(async move {
+                let result = atcoder_api::scrape_user(&atcoder_id).await.map_err(|e| e.to_string() );
+                drop(permit);
+                (user_id, atcoder_id, result)
+            });
+        }
+        let mut ratings = vec![];
+        while let Some(joined) = tasks.join_next().await {
+            match joined? {
+                (user_id, _, Ok(details)) => ratings.push((user_id, details)),
+                (_, atcoder_id, Err(reason)) => summary.failures.push((atcoder_id, reason)),
+            }
+        }
+
+        // save
+        summary.updated += ratings.len();
+        database::with_database(save_ratings, ratings).await?;
+    }
+    Ok(summary)
+}
@@ -0,0 +1,62 @@
+use std::time::SystemTime;
+
+use crate::{aggregate, atcoder_api::{self, AtCoderUserDetails}, config::{self, RatingPolicy}};
+
+/// SQL expression of the latest rating of the voter (joined as `users`) used for the editorial (joined as `editorials`),
+/// which is the heuristic rating for editorials of heuristic contests (`contests.heuristic`, algorithm if the contest has not been scraped)
+pub const LATEST_RATING_SQL: &str = "(CASE WHEN COALESCE((SELECT heuristic FROM contests WHERE contests.contest = editorials.contest), FALSE) THEN users.heuristic_rating ELSE users.rating END)";
+
+/// get the rating of the user (`None` if unrated), scraping and saving it if the cache is old (called from blocking context)
+///
+/// the heuristic rating is returned if `heuristic` is true
+pub fn get_rating(client: &mut postgres::Client, user_id: i32, atcoder_id: &str, heuristic: bool) -> Result<Option<i16>, Box<dyn std::error::Error>> {
+    Ok(get_details(client, user_id, atcoder_id)?.rating_for(heuristic))
+}
+
+/// get the ratings and contest history of the user, scraping and saving them if the cache is old (called from blocking context)
+pub fn get_details(client: &mut postgres::Client, user_id: i32, atcoder_id: &str) -> Result<AtCoderUserDetails, Box<dyn std::error::Error>> {
+    // 過去にレーティングを取得したのが EDITORIAL_VOTING_RATING_CACHE_SECS 以内ならそれを使う
+    // (通常はバッチ処理 refresh_ratings によって更新されている)
+    if let Ok(row) = client.query_one("SELECT rating, heuristic_rating, rated_contests, first_contest_time, rating_last_update FROM users WHERE id = $1", &[&user_id]) {
+        let current = AtCoderUserDetails {
+            rating: row.get::<_, Option<i16>>(0),
+            heuristic_rating: row.get::<_, Option<i16>>(1),
+            rated_contests: row.get::<_, Option<i16>>(2).unwrap_or(0),
+            first_contest_time: row.get::<_, Option<SystemTime>>(3),
+        };
+        let current_time = row.get::<_, Option<SystemTime>>(4);
+        // ratings are NULL for unrated users, rating_last_update is NULL if never fetched
+        if let Some(current_time) = current_time {
+            if SystemTime::now().duration_since(current_time)? <= config::rating_cache_duration()? {
+                return Ok(current);
+            }
+        }
+    }
+
+    // 古いなら新しく取得
+    let details = scrape_details(atcoder_id)?;
+    // 保存
+    store_rating(client, user_id, &details)?;
+    Ok(details)
+}
+
+/// scrape the ratings and contest history of the user without saving them (called from blocking context)
+pub fn scrape_details(atcoder_id: &str) -> Result<AtCoderUserDetails, Box<dyn std::error::Error>> {
+    let atcoder_id = atcoder_id.to_string();
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(async move { atcoder_api::scrape_user(&atcoder_id).await })
+}
+
+/// save the fetched ratings and contest history, moving the votes of the user to the new buckets if the rating policy is `latest`
+pub fn store_rating(client: &mut postgres::Client, user_id: i32, details: &AtCoderUserDetails) -> Result<(), Box<dyn std::error::Error>> {
+    let now_time = SystemTime::now();
+    let mut tx = client.transaction()?;
+    tx.execute("UPDATE users SET rating = $1, heuristic_rating = $2, rated_contests = $3, first_contest_time = $4, rating_last_update = $5 WHERE id = $6", &[&details.rating, &details.heuristic_rating, &details.rated_contests, &details.first_contest_time, &now_time, &user_id])?;
+    if config::rating_policy()? == RatingPolicy::Latest {
+        aggregate::rebucket_user_votes(&mut tx, user_id)?;
+    }
+    tx.commit()?;
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use std::{collections::{BTreeSet, HashMap}, time::{Duration, SystemTime}};
+
+pub struct DetectionOptions {
+    /// only votes within this duration are examined
+    pub recent: Duration,
+    /// votes in the same direction on the same target within this window are grouped
+    pub window: Duration,
+    /// users who voted within this duration since their first token are fresh accounts
+    pub fresh_age: Duration,
+    /// minimum number of fresh accounts in a group to report
+    pub min_group: usize,
+    /// minimum number of editorials voted by both users of a pair to compare
+    pub min_common: i64,
+    /// minimum ratio of the same votes among common editorials to report a pair
+    pub min_agreement: f64,
+}
+
+impl Default for DetectionOptions {
+    fn default() -> Self {
+        Self {
+            recent: Duration::from_secs(90 * 24 * 60 * 60),
+            window: Duration::from_secs(60 * 60),
+            fresh_age: Duration::from_secs(7 * 24 * 60 * 60),
+            min_group: 5,
+            min_common: 10,
+            min_agreement: 0.95,
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct DetectionSummary {
+    pub bursts: usize,
+    pub similar_pairs: usize,
+}
+
+pub struct VoteReport {
+    pub id: i32,
+    /// `burst` or `similar_pair`
+    pub kind: String,
+    pub user_ids: Vec<i32>,
+    pub details: serde_json::Value,
+    pub created_at: SystemTime,
+    /// AtCoder ID of the moderator who reviewed the report
+    pub reviewed_by: Option<String>,
+}
+
+/// detect suspicious voting patterns and save them to `vote_reports` (already reported findings are skipped)
+pub fn detect_vote_rings(client: &mut postgres::Client, options: &DetectionOptions) -> Result<DetectionSummary, Box<dyn std::error::Error>> {
+    let mut summary = DetectionSummary::default();
+    let now = SystemTime::now();
+    let since = now - options.recent;
+    let statement = client.prepare("INSERT INTO vote_reports(fingerprint, kind, user_ids, details, created_at) VALUES($1, $2, $3, $4, $5) ON CONFLICT (fingerprint) DO NOTHING")?;
+
+    // bursts: many fresh accounts voting in the same direction on the same author (or editorial without author) within a short window
+    let rows = client.query("SELECT votes.user_id, COALESCE(editorials.author, editorials.editorial), votes.score, votes.voted_at, users.created_at FROM votes JOIN users ON users.id = votes.user_id JOIN editorials ON editorials.id = votes.editorial_id WHERE votes.voted_at >= $1 AND users.created_at IS NOT NULL AND NOT users.banned", &[&since])?;
+    let mut groups = HashMap::<(String, i16), Vec<(SystemTime, i32)>>::new();
+    for row in rows {
+        let voted_at = row.get::<_, SystemTime>(3);
+        let created_at = row.get::<_, SystemTime>(4);
+        if voted_at.duration_since(created_at).unwrap_or_default() > options.fresh_age {
+            continue;
+        }
+        groups.entry((row.get::<_, String>(1), row.get::<_, i16>(2))).or_default().push((voted_at, row.get::<_, i32>(0)));
+    }
+    for ((target, score), mut votes) in groups {
+        votes.sort();
+        let mut start = 0;
+        while start < votes.len() {
+            // users voted within the window from `start`
+            let end = votes[start ..].iter().position(|&(time, _)| time.duration_since(votes[start].0).unwrap_or_default() > options.window ).map_or(votes.len(), |position| start + position );
+            let users = votes[start .. end].iter().map(|&(_, user_id)| user_id ).collect::<BTreeSet<_>>();
+            if users.len() < options.min_group {
+                start += 1;
+                continue;
+            }
+            let user_ids = users.into_iter().collect::<Vec<_>>();
+            let fingerprint = format!("burst:{target}:{score}:{user_ids:?}");
+            let details = serde_json::json!({
+                "target": target,
+                "score": score,
+                "first_vote": votes[start].0.duration_since(SystemTime::UNIX_EPOCH)?.as_secs(),
+                "last_vote": votes[end - 1].0.duration_since(SystemTime::UNIX_EPOCH)?.as_secs(),
+            });
+            summary.bursts += client.execute(&statement, &[&fingerprint, &"burst", &user_ids, &details, &now])? as usize;
+            start = end;
+        }
+    }
+
+    // similar pairs: users whose recent votes are almost the same on many editorials
+    let rows = client.query("WITH recent AS (SELECT votes.user_id, votes.editorial_id, votes.score FROM votes JOIN users ON users.id = votes.user_id WHERE votes.voted_at >= $1 AND NOT users.banned) SELECT a.user_id, b.user_id, COUNT(*), COUNT(*) FILTER (WHERE a.score = b.score) FROM recent AS a JOIN recent AS b ON a.editorial_id = b.editorial_id AND a.user_id < b.user_id GROUP BY a.user_id, b.user_id HAVING COUNT(*) >= $2", &[&since, &options.min_common])?;
+    for row in rows {
+        let user_ids = vec![row.get::<_, i32>(0), row.get::<_, i32>(1)];
+        let (common, same) = (row.get::<_, i64>(2), row.get::<_, i64>(3));
+        let agreement = same as f64 / common as f64;
+        if agreement < options.min_agreement {
+            continue;
+        }
+        let fingerprint = format!("similar_pair:{}:{}", user_ids[0], user_ids[1]);
+        let details = serde_json::json!({
+            "common": common,
+            "same": same,
+            "agreement": agreement,
+        });
+        summary.similar_pairs += client.execute(&statement, &[&fingerprint, &"similar_pair", &user_ids, &details, &now])? as usize;
+    }
+    Ok(summary)
+}
+
+/// reports ordered from the newest (only not reviewed ones unless `include_reviewed`)
+pub fn list_reports(client: &mut impl postgres::GenericClient, include_reviewed: bool, limit: i64) -> Result<Vec<VoteReport>, postgres::Error> {
+    let rows = client.query("SELECT id, kind, user_ids, details, created_at, reviewed_by FROM vote_reports WHERE $1 OR reviewed_by IS NULL ORDER BY id DESC LIMIT $2", &[&include_reviewed, &limit])?;
+    Ok(rows.into_iter().map(|row| VoteReport {
+        id: row.get(0),
+        kind: row.get(1),
+        user_ids: row.get(2),
+        details: row.get(3),
+        created_at: row.get(4),
+        reviewed_by: row.get(5),
+    }).collect())
+}
+
+/// mark the reports as reviewed by the moderator
+pub fn review_reports(client: &mut impl postgres::GenericClient, report_ids: &[i32], moderator: &str) -> Result<u64, postgres::Error> {
+    client.execute("UPDATE vote_reports SET reviewed_by = $2, reviewed_at = $3 WHERE id = ANY($1) AND reviewed_by IS NULL", &[&report_ids, &moderator, &SystemTime::now()])
+}
\ No newline at end of file
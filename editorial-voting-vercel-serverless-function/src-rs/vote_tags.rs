@@ -0,0 +1,16 @@
+/// tags voters can attach to their votes
+pub const TAGS: [&str; 5] = ["clear", "has_code", "proof_included", "contains_error", "too_terse"];
+
+/// check that all tags are in `TAGS`, and remove duplicates
+pub fn normalize_tags(tags: &[String]) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut normalized = vec![];
+    for tag in tags {
+        if !TAGS.contains(&tag.as_str()) {
+            return Err(format!("invalid tag {tag:?} ({})", TAGS.join("|")).into());
+        }
+        if !normalized.contains(tag) {
+            normalized.push(tag.clone());
+        }
+    }
+    Ok(normalized)
+}
\ No newline at end of file